@@ -0,0 +1,84 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use tui_tetris::bot::Bot;
+use tui_tetris::config::Rules;
+use tui_tetris::simulate;
+use tui_tetris::tetris::{Input, Mode, RotationSystem, Tetris};
+
+const WIDTH: usize = 10;
+const HEIGHT: usize = 20;
+
+fn rules(rotation: RotationSystem) -> Rules {
+    Rules { width: WIDTH, height: HEIGHT, rotation, ..Rules::default() }
+}
+
+// a game some turns in, for a board that is not empty
+fn midgame(rotation: RotationSystem) -> Tetris {
+    let bot = Bot::default();
+    let mut game = Tetris::new(&rules(rotation), Mode::Marathon, 1);
+    game.set_elapsed_ms(0);
+    for _ in 0..20 {
+        for input in bot.plan(&game).expect("the game goes on").inputs {
+            game.apply(input);
+        }
+    }
+    game
+}
+
+fn collision(c: &mut Criterion) {
+    let mut game = midgame(RotationSystem::Classic);
+    let piece = game.current_piece.clone();
+
+    c.bench_function("engine/collides", |b| b.iter(|| {
+        (-5..5).flat_map(|x| (0..HEIGHT as i16).map(move |y| (x, y)))
+            .filter(|&offset| game.collides(black_box(&piece), offset)).count()
+    }));
+
+    // projecting again from the same spot does the same work every time
+    c.bench_function("engine/project", |b| b.iter(|| {
+        game.project();
+        game.projected_piece.anchor_y
+    }));
+}
+
+fn rotation(c: &mut Criterion) {
+    for system in RotationSystem::ALL {
+        let game = midgame(system);
+        let piece = game.current_piece.clone();
+        let name = format!("engine/rotated/{:?}", system).to_lowercase();
+        c.bench_function(&name, |b| b.iter(|| {
+            (1..4).filter_map(|turns| game.rotated(black_box(&piece), turns)).count()
+        }));
+    }
+}
+
+fn games(c: &mut Criterion) {
+    let rules = rules(RotationSystem::Srs);
+
+    // the engine alone, every piece hard dropped where it spawns
+    c.bench_function("game/hard_drops", |b| b.iter(|| {
+        let mut game = Tetris::new(&rules, Mode::Marathon, black_box(7));
+        game.set_elapsed_ms(0);
+        while !game.is_over() {
+            game.apply(Input::HardDrop);
+        }
+        game.score
+    }));
+
+    let mut group = c.benchmark_group("game");
+    group.sample_size(10);
+    group.bench_function("bot_100_pieces", |b| b.iter(|| {
+        simulate::play(&mut Bot::default(), &rules, Mode::Marathon, black_box(7), 100)
+            .expect("the built in bot never fails")
+    }));
+    group.finish();
+}
+
+fn bot(c: &mut Criterion) {
+    let bot = Bot::default();
+    let game = midgame(RotationSystem::Classic);
+    c.bench_function("bot/plan", |b| b.iter(|| bot.plan(black_box(&game))));
+}
+
+criterion_group!(benches, collision, rotation, games, bot);
+criterion_main!(benches);
@@ -0,0 +1,136 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+
+use tui_tetris::piece::{Piece, PieceType};
+use tui_tetris::pile::Pile;
+
+const WIDTH: usize = 10;
+const HEIGHT: usize = 20;
+
+fn square(column: usize, row: usize) -> Piece {
+    Piece::new(PieceType::Square, column as i16, row as i16)
+}
+
+// the pile as it was before it kept a bitmask per row: a grid of cells and a map of
+// their colors. It is only here to compare against.
+mod grid {
+    use std::collections::{HashMap, HashSet};
+
+    use tui_tetris::matrix::Matrix;
+    use tui_tetris::piece::{self, Piece, PieceColor};
+
+    #[derive(Clone)]
+    pub struct Pile {
+        field: Matrix,
+        map: HashMap<(usize, usize), PieceColor>,
+    }
+
+    impl Pile {
+        pub fn contains(&self, coords: (usize, usize)) -> bool {
+            self.field[coords]
+        }
+
+        pub fn add(&mut self, piece: &Piece) {
+            for coords in piece.get_positions().iter().cloned() {
+                self.field[coords] = true;
+                self.map.insert(coords, piece::get_piece_color(&piece.piece_type));
+            }
+        }
+
+        fn is_complete_line_with(&self, line: usize, additional: &HashSet<(usize, usize)>)
+            -> bool {
+            self.field.get_row(line).iter().enumerate()
+                .all(|(idx, value)| *value || additional.contains(&(line, idx)))
+        }
+
+        pub fn get_complete_lines_with(&self, positions: &[(usize, usize); 4]) -> Vec<usize> {
+            let positions_set: HashSet<(usize, usize)> = positions.iter().cloned().collect();
+            positions.iter().map(|(i, _)| *i)
+                .filter(|i| self.is_complete_line_with(*i, &positions_set)).collect()
+        }
+
+        fn copy_line(&mut self, from: usize, to: usize) {
+            self.field.copy_row(from, to);
+            for (idx, value) in self.field.get_row(to).iter().enumerate() {
+                if *value {
+                    self.map.insert((to, idx),
+                        self.map.get(&(from, idx)).cloned().unwrap_or(PieceColor::Green));
+                }
+            }
+        }
+
+        pub fn cleanup_full_lines(&mut self) -> usize {
+            let mut current = self.field.row_count - 1;
+            let mut cleaned_up = 0;
+            for line in (1..self.field.row_count).rev() {
+                if self.is_complete_line_with(line, &HashSet::new()) {
+                    self.remove_line(line);
+                    cleaned_up += 1;
+                    continue;
+                }
+                if line != current {
+                    self.copy_line(line, current);
+                    self.remove_line(line);
+                }
+                current -= 1;
+            }
+            for line in 0..current {
+                self.remove_line(line);
+            }
+            cleaned_up
+        }
+
+        fn remove_line(&mut self, line: usize) {
+            for (idx, value) in self.field.get_row_mut(line).iter_mut().enumerate() {
+                *value = false;
+                self.map.remove(&(line, idx));
+            }
+        }
+
+        pub fn new(col_count: usize, row_count: usize) -> Self {
+            Pile { field: Matrix::new(col_count, row_count), map: HashMap::new() }
+        }
+    }
+}
+
+// the same operations on both piles, to see what the bitmask rows won. The bottom four rows
+// are filled with O pieces but for the last two columns, and the O that is added clears the
+// bottom two rows.
+macro_rules! bench_piles {
+    ($c:expr, $name:expr, $pile:ty) => {{
+        let mut group = $c.benchmark_group($name);
+        let mut pile = <$pile>::new(WIDTH, HEIGHT);
+        for row in [HEIGHT - 4, HEIGHT - 2] {
+            for column in (0..WIDTH - 2).step_by(2) {
+                pile.add(&square(column, row));
+            }
+        }
+        let piece = square(WIDTH - 2, HEIGHT - 2);
+
+        group.bench_function("add_and_clear", |b| b.iter_batched(
+            || pile.clone(),
+            |mut pile| {
+                pile.add(black_box(&piece));
+                pile.cleanup_full_lines()
+            },
+            BatchSize::SmallInput));
+
+        group.bench_function("complete_lines_with", |b| b.iter(|| {
+            pile.get_complete_lines_with(black_box(&piece.get_positions()))
+        }));
+
+        group.bench_function("contains", |b| b.iter(|| {
+            let pile = black_box(&pile);
+            (0..HEIGHT).flat_map(|i| (0..WIDTH).map(move |j| (i, j)))
+                .filter(|&coords| pile.contains(coords)).count()
+        }));
+        group.finish();
+    }};
+}
+
+fn piles(c: &mut Criterion) {
+    bench_piles!(c, "pile", Pile);
+    bench_piles!(c, "grid_pile", grid::Pile);
+}
+
+criterion_group!(benches, piles);
+criterion_main!(benches);
@@ -0,0 +1,82 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use tui::backend::TestBackend;
+use tui::Terminal;
+
+use tui_tetris::bot::Bot;
+use tui_tetris::config::{Renderer, Rules, Visuals};
+use tui_tetris::tetris::{Mode, Tetris};
+use tui_tetris::ui;
+
+// counts allocations so every benchmark can say how many a frame makes
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+// the first frame also fills the buffer, count the one after
+fn allocations(mut f: impl FnMut()) -> usize {
+    f();
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    f();
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+// the default board some turns in, with a pile to draw
+fn midgame() -> Tetris {
+    let bot = Bot::default();
+    let mut game = Tetris::new(&Rules::default(), Mode::Marathon, 1);
+    game.set_elapsed_ms(0);
+    for _ in 0..40 {
+        for input in bot.plan(&game).expect("the game goes on").inputs {
+            game.apply(input);
+        }
+    }
+    game
+}
+
+fn board(c: &mut Criterion) {
+    let game = midgame();
+    let mut terminal = Terminal::new(TestBackend::new(200, 60)).expect("test backend");
+
+    for renderer in Renderer::ALL {
+        let visuals = Visuals { renderer, ..Visuals::default() };
+        let name = format!("render/board/{:?}", renderer).to_lowercase();
+        let mut draw = || {
+            let mut frame = terminal.get_frame();
+            let area = frame.size();
+            ui::draw_board(&mut frame, area, &game, &visuals);
+        };
+        println!("{}: {} allocations per frame", name, allocations(&mut draw));
+        c.bench_function(&name, |b| b.iter(&mut draw));
+    }
+}
+
+// a whole frame as the game draws it, diffed against the last one and written out
+fn frame(c: &mut Criterion) {
+    let game = midgame();
+    let mut terminal = Terminal::new(TestBackend::new(200, 60)).expect("test backend");
+    let visuals = Visuals::default();
+
+    let mut draw = || terminal.draw(|f| ui::draw_game(f, &game, &visuals)).expect("test backend");
+    println!("render/frame: {} allocations per frame", allocations(&mut draw));
+    c.bench_function("render/frame", |b| b.iter(&mut draw));
+}
+
+criterion_group!(benches, board, frame);
+criterion_main!(benches);
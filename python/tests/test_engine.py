@@ -0,0 +1,87 @@
+# run after `maturin develop --features python`, or with the module built by
+# `cargo build --release --features python` copied next to this file as tui_tetris.so:
+#   python -m unittest discover python/tests
+
+import unittest
+
+import tui_tetris
+
+
+def play(game, actions):
+    return [game.step(action) for action in actions]
+
+
+class GameTest(unittest.TestCase):
+    def test_observation(self):
+        game = tui_tetris.Game(seed=1, width=10, height=20, preview=5)
+        observation = game.observation()
+        self.assertEqual(len(observation["board"]), 20)
+        self.assertTrue(all(len(row) == 10 for row in observation["board"]))
+        self.assertFalse(any(any(row) for row in observation["board"]))
+        self.assertEqual(len(observation["queue"]), 5)
+        self.assertIn(observation["current"], "IOTLJSZ")
+        self.assertEqual(len(observation["current_cells"]), 4)
+        self.assertTrue(observation["can_hold"])
+
+    def test_same_seed_same_game(self):
+        actions = ["move_left", "rotate_cw", "hard_drop", "hold", "move_right", "hard_drop"] * 5
+        first = tui_tetris.Game(seed=7)
+        second = tui_tetris.Game(seed=7)
+        self.assertEqual(play(first, actions), play(second, actions))
+        self.assertEqual(first.board(), second.board())
+
+    def test_reset(self):
+        game = tui_tetris.Game(seed=3)
+        start = game.observation()
+        play(game, ["hard_drop"] * 3)
+        self.assertEqual(game.pieces, 3)
+        self.assertEqual(game.reset(3), start)
+        self.assertEqual(game.pieces, 0)
+
+    def test_placements(self):
+        game = tui_tetris.Game(seed=2, width=10)
+        placements = game.placements()
+        self.assertTrue(placements)
+        for placement in placements:
+            self.assertEqual(placement["inputs"][-1], "hard_drop")
+            self.assertEqual(placement["hold"], placement["inputs"][0] == "hold")
+            for action in placement["inputs"]:
+                self.assertIn(action, tui_tetris.INPUTS)
+
+        target = placements[len(placements) // 2]
+        game.step(len(placements) // 2)
+        self.assertEqual(game.pieces, 1)
+        board = game.board()
+        self.assertTrue(all(board[row][column] for row, column in target["cells"]))
+
+    def test_line_reward(self):
+        game = tui_tetris.Game(seed=5, width=4, height=10, rewards={"lines": 2.0, "survival": 0.5})
+        total, survived = 0.0, 0
+        for _ in range(200):
+            placements = game.placements()
+            # greedy: the placement that leaves the lowest stack
+            best = max(range(len(placements)),
+                       key=lambda idx: min(row for row, _ in placements[idx]["cells"]))
+            _, reward, done = game.step(best)
+            total += reward
+            if done:
+                break
+            survived += 1
+        # the step that tops out gets the top_out reward instead of survival
+        self.assertEqual(total, 2.0 * game.lines + 0.5 * survived)
+        self.assertGreater(game.lines, 0)
+
+    def test_bad_actions(self):
+        game = tui_tetris.Game()
+        with self.assertRaises(ValueError):
+            game.step("teleport")
+        with self.assertRaises(IndexError):
+            game.step(100000)
+        with self.assertRaises(ValueError):
+            tui_tetris.Game(width=1)
+        with self.assertRaises(ValueError):
+            tui_tetris.Game(mode="zen")
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,149 @@
+// effects drawn over the board for what the game reports, they run on the wall clock
+// and never hold up the game
+
+use std::time::{Duration, Instant};
+
+use crate::tetris::GameEvent;
+
+// time between two frames while something is animated
+pub const FRAME_MS: u64 = 30;
+
+const WIPE_MS: u64 = 250;
+const LOCK_MS: u64 = 150;
+// the board flashes a few times for a tetris or a T-spin
+const FLASH_MS: u64 = 300;
+const FLASH_BLINK_MS: u64 = 75;
+const CALLOUT_MS: u64 = 900;
+// a callout rises one row every this many ms
+const CALLOUT_RISE_MS: u64 = 150;
+
+enum Effect {
+    // cleared rows, shrinking from the edges to the middle of the board for as long as
+    // the line clear delay if there is one
+    Wipe(Vec<usize>, Duration),
+    Lock(Vec<(usize, usize)>),
+    Flash,
+    Callout(String, usize),
+}
+
+impl Effect {
+    fn duration(&self) -> Duration {
+        match self {
+            Effect::Wipe(_, duration) => *duration,
+            Effect::Lock(_) => Duration::from_millis(LOCK_MS),
+            Effect::Flash => Duration::from_millis(FLASH_MS),
+            Effect::Callout(..) => Duration::from_millis(CALLOUT_MS),
+        }
+    }
+}
+
+// what the animations put on the board at one moment
+#[derive(Default)]
+pub struct Overlay {
+    // rows being wiped and how far along the wipe is, from 0 to 1
+    pub wipes: Vec<(usize, f32)>,
+    // the piece that was just locked
+    pub locked: Vec<(usize, usize)>,
+    // the border is lit up
+    pub flash: bool,
+    // text over the board and the row it is on
+    pub callouts: Vec<(String, usize)>,
+}
+
+impl Overlay {
+    pub fn wipes(&self, row: usize) -> Option<f32> {
+        self.wipes.iter().find(|(wiped, _)| *wiped == row).map(|(_, progress)| *progress)
+    }
+}
+
+// how a clear is called out, like "+800 B2B TETRIS"
+pub fn callout(lines: usize, t_spin: bool, back_to_back: bool, points: usize) -> String {
+    let name = match (t_spin, lines) {
+        (true, 0) => "T-SPIN",
+        (true, 1) => "T-SPIN SINGLE",
+        (true, 2) => "T-SPIN DOUBLE",
+        (true, _) => "T-SPIN TRIPLE",
+        (false, 1) => "SINGLE",
+        (false, 2) => "DOUBLE",
+        (false, 3) => "TRIPLE",
+        (false, _) => "TETRIS",
+    };
+    let prefix = if back_to_back { "B2B " } else { "" };
+    match points {
+        0 => format!("{}{}", prefix, name),
+        points => format!("+{} {}{}", points, prefix, name),
+    }
+}
+
+#[derive(Default)]
+pub struct Animations {
+    effects: Vec<(Instant, Effect)>,
+    // the row the last lock or callout was on, the next callout goes near it
+    last_row: usize,
+}
+
+impl Animations {
+    pub fn new() -> Self {
+        Animations::default()
+    }
+
+    pub fn start(&mut self, event: GameEvent, now: Instant) {
+        match event {
+            GameEvent::Locked { cells } => {
+                self.last_row = cells.iter().map(|(i, _)| *i).min().unwrap_or(self.last_row);
+                self.effects.push((now, Effect::Lock(cells)));
+            }
+            GameEvent::Cleared { rows, t_spin, back_to_back, points, delay_ms } => {
+                if t_spin || rows.len() >= 4 {
+                    self.effects.push((now, Effect::Flash));
+                }
+                let row = rows.first().copied().unwrap_or(self.last_row);
+                self.last_row = row;
+                let text = callout(rows.len(), t_spin, back_to_back, points);
+                self.effects.push((now, Effect::Callout(text, row)));
+                if !rows.is_empty() {
+                    let wipe_ms = if delay_ms > 0 { delay_ms } else { WIPE_MS };
+                    self.effects.push((now, Effect::Wipe(rows, Duration::from_millis(wipe_ms))));
+                }
+            }
+            GameEvent::LevelUp { level } => {
+                self.effects.push((now, Effect::Flash));
+                // above the callout of the clear that got there
+                let row = self.last_row.saturating_sub(2);
+                self.effects.push((now, Effect::Callout(format!("LEVEL {}", level), row)));
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.effects.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+
+    // drops the effects that are over and puts the rest together
+    pub fn overlay(&mut self, now: Instant) -> Overlay {
+        self.effects.retain(|(started, effect)| now.duration_since(*started) < effect.duration());
+
+        let mut overlay = Overlay::default();
+        for (started, effect) in self.effects.iter() {
+            let elapsed = now.duration_since(*started);
+            let ms = elapsed.as_millis() as u64;
+            match effect {
+                Effect::Wipe(rows, _) => {
+                    let progress = elapsed.as_secs_f32() / effect.duration().as_secs_f32();
+                    overlay.wipes.extend(rows.iter().map(|row| (*row, progress)));
+                }
+                Effect::Lock(cells) => overlay.locked.extend_from_slice(cells),
+                Effect::Flash => overlay.flash |= (ms / FLASH_BLINK_MS).is_multiple_of(2),
+                Effect::Callout(text, row) => {
+                    let row = row.saturating_sub((ms / CALLOUT_RISE_MS) as usize);
+                    overlay.callouts.push((text.clone(), row));
+                }
+            }
+        }
+        overlay
+    }
+}
@@ -0,0 +1,748 @@
+use tui::backend::Backend;
+use tui::Terminal;
+
+use std::collections::VecDeque;
+use std::io;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use crate::animation::{self, Animations};
+use crate::bot::{self, Player};
+use crate::cli::Overrides;
+use crate::config::{Config, GravityPreset, Renderer, Rules, Theme};
+use crate::controls::{Action, KeyMap};
+use crate::crash;
+use crate::highscores::{self, HighScores};
+use crate::input::{AutoShift, Event, Events, HeldActions, Key, Shift};
+use crate::keybinding_editor::{EditorOutcome, KeyBindingEditor};
+use crate::menu::{Menu, MenuEvent};
+use crate::replay::{self, Replay};
+use crate::terminal;
+use crate::tetris::{Input, Mode, RotationSystem, Tetris};
+use crate::theme::ColorDepth;
+use crate::ui;
+
+// time between two inputs of the bot, so it can be watched
+const BOT_STEP_MS: u64 = 40;
+
+// a game in progress together with its recording
+pub struct Session {
+    pub game: Tetris,
+    pub recording: Replay,
+}
+
+impl Session {
+    pub fn new(rules: &Rules, mode: Mode, seed: u64) -> Self {
+        let recording = Replay::new(seed, mode, rules);
+        crash::start_game(&recording);
+        Session { game: Tetris::new(rules, mode, seed), recording }
+    }
+
+    // continues a recorded game from where it was left
+    pub fn resume(recording: Replay) -> Self {
+        let mut game = recording.play_all();
+        game.follow_wall_clock();
+        // nothing to animate from the part that was played already
+        game.take_events().for_each(drop);
+        crash::start_game(&recording);
+        Session { game, recording }
+    }
+
+    fn play(&mut self, input: Input) {
+        self.recording.record(self.game.elapsed_ms(), input, false);
+        crash::record(self.game.elapsed_ms(), input, false);
+        self.game.apply(input);
+    }
+
+    fn play_repeat(&mut self, input: Input) {
+        self.recording.record(self.game.elapsed_ms(), input, true);
+        crash::record(self.game.elapsed_ms(), input, true);
+        self.game.apply_repeat(input);
+    }
+
+    // lets the game catch up with its clock. A rotation or hold held down since the game
+    // started waiting, or still held, is done as the next piece comes in (IRS and IHS), the
+    // inputs that went to the piece are returned
+    fn catch_up(&mut self, held: &HeldActions, since: Instant, now: Instant) -> Vec<Input> {
+        let game = &self.game;
+        let mut inputs = Vec::new();
+        if let Some(at_ms) = game.spawn_at_ms().filter(|at_ms| *at_ms <= game.elapsed_ms()) {
+            inputs = held.held(since, now)
+                .filter_map(|action| action.input())
+                .filter(|input| input.modifies_spawn()).collect();
+            // all kept for the piece, which comes in with the update. They are recorded
+            // from just before it, a replay keeps them for the piece too.
+            for input in inputs.iter() {
+                self.recording.record(at_ms - 1, *input, false);
+                crash::record(at_ms - 1, *input, false);
+                self.game.keep_for_spawn(*input);
+            }
+        }
+        self.game.update();
+        inputs
+    }
+
+    // a key was pressed, false when there is nothing more to do with it: it went to the
+    // next piece, or there is nothing to move between two pieces
+    pub fn press(&mut self, held: &mut HeldActions, action: Action, since: Instant,
+                 now: Instant) -> bool {
+        held.press(action, now);
+        let used = self.catch_up(held, since, now);
+        match action.input() {
+            Some(input) => !used.contains(&input) && self.game.delay.is_none(),
+            None => true,
+        }
+    }
+
+    fn shift(&mut self, shift: Shift, input: Input) {
+        let cells = match shift {
+            Shift::None => 0,
+            Shift::Press => return self.play(input),
+            Shift::Cells(cells) => cells,
+            Shift::ToWall => self.game.width(),
+        };
+        for _ in 0..cells {
+            let before = self.game.current_piece.anchor_x;
+            self.play_repeat(input);
+            if self.game.current_piece.anchor_x == before {
+                break;
+            }
+        }
+    }
+}
+
+enum PauseChoice {
+    Resume,
+    Restart,
+    Quit,
+}
+
+pub enum Screen {
+    Title,
+    ModeSelect,
+    Settings,
+    HighScores,
+    Playing(Box<Session>),
+    GameOver(Box<Session>, Option<usize>),
+    Quit,
+}
+
+pub struct App<B: Backend> {
+    // as loaded from the config file and changed in the settings, this is what gets saved
+    pub config: Config,
+    pub config_path: Option<PathBuf>,
+    pub terminal: Terminal<B>,
+    pub events: Events,
+    // a fixed seed from the command line, every game gets a random one otherwise
+    pub seed: Option<u64>,
+    // where to write the replay of each finished game
+    pub record_path: Option<PathBuf>,
+    // rules from the command line, on top of the config for every game
+    pub overrides: Overrides,
+    // the built-in bot plays instead of the player when set
+    pub bot: Option<Box<dyn Player>>,
+    high_scores: HighScores,
+    status: Option<String>,
+}
+
+// SIGTERM and SIGHUP come out of the screens as this error, after the game in play
+// is saved. They end the program the same way quitting does.
+fn terminated(err: &io::Error) -> bool {
+    err.kind() == io::ErrorKind::Interrupted
+}
+
+fn cycle<T: PartialEq + Copy>(values: &[T], current: T, forward: bool) -> T {
+    let idx = values.iter().position(|value| *value == current).unwrap_or(0);
+    let next = if forward { idx + 1 } else { idx + values.len() - 1 };
+    values[next % values.len()]
+}
+
+// the name the setting has in the config file
+fn setting_name<T: std::fmt::Debug + serde::Serialize>(value: T) -> String {
+    match serde_json::to_value(&value) {
+        Ok(serde_json::Value::String(name)) => name,
+        _ => format!("{:?}", value).to_lowercase(),
+    }
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value { "on" } else { "off" }
+}
+
+impl<B: Backend> App<B> {
+    pub fn new(config: Config, config_path: Option<PathBuf>, terminal: Terminal<B>,
+               events: Events) -> Self {
+        let (high_scores, status) = match highscores::default_path()
+            .map(|path| HighScores::load(&path)).transpose() {
+            Ok(high_scores) => (high_scores.unwrap_or_default(), None),
+            Err(err) => (HighScores::default(),
+                         Some(format!("Failed to load high scores: {}", err))),
+        };
+        App { config, config_path, terminal, events, seed: None, record_path: None,
+              overrides: Overrides::default(), bot: None, high_scores, status }
+    }
+
+    pub fn run(&mut self, mut screen: Screen) -> io::Result<()> {
+        loop {
+            let next = match screen {
+                Screen::Title => self.title(),
+                Screen::ModeSelect => self.mode_select(),
+                Screen::Settings => self.settings(),
+                Screen::HighScores => self.show_high_scores(),
+                Screen::Playing(session) => self.play(*session),
+                Screen::GameOver(session, place) => self.game_over(*session, place),
+                Screen::Quit => return Ok(()),
+            };
+            screen = match next {
+                Err(err) if terminated(&err) => return Ok(()),
+                next => next?,
+            };
+        }
+    }
+
+    // the rules games are played with
+    pub fn rules(&self) -> Rules {
+        let mut rules = self.config.rules.clone();
+        self.overrides.apply(&mut rules);
+        rules
+    }
+
+    pub fn new_session(&self, mode: Mode) -> Session {
+        Session::new(&self.rules(), mode, self.seed.unwrap_or_else(rand::random))
+    }
+
+    // None when there is no key but the screen has to be drawn again
+    fn next_key(&mut self) -> io::Result<Option<Key>> {
+        let event = self.events.receiver.recv()
+            .map_err(|err| io::Error::new(io::ErrorKind::BrokenPipe, err))?;
+        match event {
+            Event::Key(key) => Ok(Some(key)),
+            Event::Redraw => Ok(None),
+            Event::Suspend => self.suspend().map(|_| None),
+            Event::Terminate =>
+                Err(io::Error::new(io::ErrorKind::Interrupted, "terminated by a signal")),
+        }
+    }
+
+    // gives the terminal back to the shell until the game is continued
+    fn suspend(&mut self) -> io::Result<()> {
+        self.terminal.show_cursor()?;
+        terminal::suspend()?;
+        // the screen could have been anything in the meantime
+        self.terminal.clear()
+    }
+
+    fn take_status(&mut self) -> Vec<String> {
+        self.status.take().into_iter().collect()
+    }
+
+    fn title(&mut self) -> io::Result<Screen> {
+        let saved = replay::autosave_path().filter(|path| path.exists());
+        let mut items = vec!["Play", "Settings", "High scores", "Quit"];
+        if saved.is_some() {
+            items.insert(0, "Resume");
+        }
+        let labels: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+        let footer = self.take_status();
+        let mut menu = Menu::new();
+
+        loop {
+            let selected = Some(menu.selected);
+            self.terminal.draw(|f| ui::draw_menu(f, "Tetris", &labels, selected, &footer))?;
+            let key = match self.next_key()? {
+                Some(key) => key,
+                None => continue,
+            };
+            match menu.handle_key(key, items.len()) {
+                MenuEvent::Chosen(idx) => return Ok(match items[idx] {
+                    "Resume" => match saved.as_deref().map(Replay::load) {
+                        Some(Ok(recording)) =>
+                            Screen::Playing(Box::new(Session::resume(recording))),
+                        Some(Err(err)) => {
+                            self.status = Some(format!("Failed to load the saved game: {}", err));
+                            Screen::Title
+                        }
+                        None => Screen::Title,
+                    },
+                    "Play" => Screen::ModeSelect,
+                    "Settings" => Screen::Settings,
+                    "High scores" => Screen::HighScores,
+                    _ => Screen::Quit,
+                }),
+                MenuEvent::Back => return Ok(Screen::Quit),
+                _ => (),
+            }
+        }
+    }
+
+    fn mode_select(&mut self) -> io::Result<Screen> {
+        let labels: Vec<String> = Mode::ALL.iter().map(|mode| mode.to_string()).collect();
+        let mut menu = Menu::new();
+
+        loop {
+            let footer = [Mode::ALL[menu.selected].description().to_string()];
+            let selected = Some(menu.selected);
+            self.terminal.draw(|f| ui::draw_menu(f, "Mode", &labels, selected, &footer))?;
+            let key = match self.next_key()? {
+                Some(key) => key,
+                None => continue,
+            };
+            match menu.handle_key(key, labels.len()) {
+                MenuEvent::Chosen(idx) =>
+                    return Ok(Screen::Playing(Box::new(self.new_session(Mode::ALL[idx])))),
+                MenuEvent::Back => return Ok(Screen::Title),
+                _ => (),
+            }
+        }
+    }
+
+    fn settings(&mut self) -> io::Result<Screen> {
+        self.edit_settings()?;
+        self.status = Some(self.save_config());
+        Ok(Screen::Title)
+    }
+
+    fn edit_settings(&mut self) -> io::Result<()> {
+        const ITEMS: usize = 10;
+        let mut menu = Menu::new();
+
+        loop {
+            let rules = &self.config.rules;
+            let labels = vec![
+                format!("Gravity: {}", setting_name(rules.gravity)),
+                format!("Rotation: {}", setting_name(rules.rotation)),
+                format!("Preview: {}", rules.preview_count),
+                format!("Theme: {}", setting_name(self.config.visuals.theme)),
+                format!("Renderer: {}", setting_name(self.config.visuals.renderer)),
+                format!("Colors: {}", setting_name(self.config.visuals.color_depth)),
+                format!("Patterns: {}", on_off(self.config.visuals.patterns)),
+                format!("Animations: {}", on_off(self.config.visuals.animations)),
+                "Key bindings".to_string(),
+                "Back".to_string(),
+            ];
+            let footer = ["Left/right to change".to_string()];
+            let selected = Some(menu.selected);
+            self.terminal.draw(|f| ui::draw_menu(f, "Settings", &labels, selected, &footer))?;
+
+            let key = match self.next_key()? {
+                Some(key) => key,
+                None => continue,
+            };
+            let (item, forward) = match menu.handle_key(key, ITEMS) {
+                MenuEvent::Chosen(8) => {
+                    self.edit_key_bindings()?;
+                    continue;
+                }
+                MenuEvent::Chosen(9) | MenuEvent::Back => break,
+                MenuEvent::Chosen(item) | MenuEvent::Next(item) => (item, true),
+                MenuEvent::Previous(item) => (item, false),
+                MenuEvent::None => continue,
+            };
+
+            let rules = &mut self.config.rules;
+            match item {
+                0 => rules.gravity = cycle(&GravityPreset::ALL, rules.gravity, forward),
+                1 => rules.rotation = cycle(&RotationSystem::ALL, rules.rotation, forward),
+                2 => {
+                    let counts: Vec<usize> = (1..=6).collect();
+                    rules.preview_count = cycle(&counts, rules.preview_count, forward);
+                }
+                3 => {
+                    let visuals = &mut self.config.visuals;
+                    visuals.theme = cycle(&Theme::ALL, visuals.theme, forward);
+                }
+                4 => {
+                    let visuals = &mut self.config.visuals;
+                    visuals.renderer = cycle(&Renderer::ALL, visuals.renderer, forward);
+                }
+                5 => {
+                    let visuals = &mut self.config.visuals;
+                    visuals.color_depth = cycle(&ColorDepth::ALL, visuals.color_depth, forward);
+                }
+                6 => self.config.visuals.patterns = !self.config.visuals.patterns,
+                7 => self.config.visuals.animations = !self.config.visuals.animations,
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    fn show_high_scores(&mut self) -> io::Result<Screen> {
+        let mut mode = Mode::Marathon;
+
+        loop {
+            let mut lines: Vec<String> = self.high_scores.entries(mode).iter().enumerate()
+                .map(|(place, entry)| format!("{:>2}. {:>8} {:>4} lines {:>9}",
+                                              place + 1, entry.score, entry.lines,
+                                              ui::format_time(entry.time_ms)))
+                .collect();
+            if lines.is_empty() {
+                lines.push("No games yet".to_string());
+            }
+            let footer = ["Left/right to switch modes".to_string()];
+            let title = format!("High scores - {}", mode);
+            self.terminal.draw(|f| ui::draw_menu(f, &title, &lines, None, &footer))?;
+
+            match self.next_key()? {
+                Some(Key::Left | Key::Char('h')) => mode = cycle(&Mode::ALL, mode, false),
+                Some(Key::Right | Key::Char('l')) => mode = cycle(&Mode::ALL, mode, true),
+                Some(Key::Esc | Key::Char('q') | Key::Char('\n')) => return Ok(Screen::Title),
+                _ => (),
+            }
+        }
+    }
+
+    fn game_over(&mut self, session: Session, place: Option<usize>) -> io::Result<Screen> {
+        let items = ["Retry".to_string(), "Menu".to_string(), "Quit".to_string()];
+        let mut footer = vec![ui::summary(&session.game), String::new()];
+        footer.extend(ui::stats_lines(&session.game));
+        if let Some(place) = place {
+            footer.push(format!("New high score! #{}", place + 1));
+        }
+        footer.extend(self.take_status());
+        let mut menu = Menu::new();
+        let visuals = self.config.visuals.clone();
+
+        loop {
+            self.terminal.draw(|f| {
+                ui::draw_game(f, &session.game, &visuals);
+                ui::draw_menu(f, "Game over", &items, Some(menu.selected), &footer);
+            })?;
+            let key = match self.next_key()? {
+                Some(key) => key,
+                None => continue,
+            };
+            match menu.handle_key(key, items.len()) {
+                MenuEvent::Chosen(0) =>
+                    return Ok(Screen::Playing(Box::new(self.new_session(session.game.mode)))),
+                MenuEvent::Chosen(1) | MenuEvent::Back => return Ok(Screen::Title),
+                MenuEvent::Chosen(_) => return Ok(Screen::Quit),
+                _ => (),
+            }
+        }
+    }
+
+    fn play(&mut self, mut session: Session) -> io::Result<Screen> {
+        let mut auto_shift = AutoShift::new(&self.config.handling);
+        // inputs the bot still has to make for the current piece, and where they lead
+        let mut planned = VecDeque::new();
+        let mut target = None;
+        let mut animations = Animations::new();
+        let mut held = HeldActions::new();
+        // when the game started waiting for the next piece
+        let mut waiting_since = None;
+
+        loop {
+            let visuals = &self.config.visuals;
+            let now = Instant::now();
+            session.catch_up(&held, waiting_since.unwrap_or(now), now);
+            waiting_since = session.game.delay.as_ref().map(|_| waiting_since.unwrap_or(now));
+            for event in session.game.take_events() {
+                if visuals.animations {
+                    animations.start(event, now);
+                }
+            }
+            let overlay = animations.overlay(now);
+            self.terminal.draw(|f| ui::draw_animated_game(f, &session.game, visuals, &overlay))?;
+
+            if session.game.is_over() {
+                return self.finish(session);
+            }
+            let mut timeout = session.game.get_timeout() as u64;
+            if self.bot.is_some() {
+                timeout = timeout.min(BOT_STEP_MS);
+            }
+            if !animations.is_empty() {
+                timeout = timeout.min(animation::FRAME_MS);
+            }
+            match self.events.receiver.recv_timeout(Duration::from_millis(timeout)) {
+                Ok(Event::Redraw) => (),
+                // the game is saved for resuming, as if the player quit
+                Ok(Event::Terminate) => {
+                    self.leave(session)?;
+                    return Ok(Screen::Quit);
+                }
+                // stops the clock before handing the terminal over, and comes back paused
+                Ok(Event::Suspend) => {
+                    session.game.pause();
+                    self.suspend()?;
+                    match self.pause(&mut session.game) {
+                        Ok(PauseChoice::Resume) => (),
+                        Ok(PauseChoice::Restart) => {
+                            session = self.new_session(session.game.mode);
+                            planned.clear();
+                            animations.clear();
+                        }
+                        Ok(PauseChoice::Quit) => return self.leave(session),
+                        Err(err) => return self.save_before(err, session),
+                    }
+                }
+                Ok(Event::Key(key)) => {
+                    let action = match self.config.keys.action_for(key) {
+                        Some(action) => action,
+                        None => continue,
+                    };
+                    // the player can still pause or quit while the bot plays
+                    if self.bot.is_some() && action.input().is_some() {
+                        continue;
+                    }
+                    // what is held between two pieces is played as the next piece comes in
+                    let now = Instant::now();
+                    if !session.press(&mut held, action, waiting_since.unwrap_or(now), now) {
+                        continue;
+                    }
+                    if action != Action::MoveLeft && action != Action::MoveRight {
+                        auto_shift.release();
+                    }
+                    match action {
+                        Action::Quit => return self.leave(session),
+                        Action::MoveLeft | Action::MoveRight => {
+                            let shift = auto_shift.on_move(key, Instant::now());
+                            if let Some(input) = action.input() {
+                                session.shift(shift, input);
+                            }
+                        }
+                        Action::Restart => {
+                            session = self.new_session(session.game.mode);
+                            planned.clear();
+                            animations.clear();
+                        }
+                        Action::Pause => match self.pause(&mut session.game) {
+                            Ok(PauseChoice::Resume) => (),
+                            Ok(PauseChoice::Restart) => {
+                                session = self.new_session(session.game.mode);
+                                planned.clear();
+                                animations.clear();
+                            }
+                            Ok(PauseChoice::Quit) => return self.leave(session),
+                            Err(err) => return self.save_before(err, session),
+                        },
+                        action => {
+                            if let Some(input) = action.input() {
+                                session.play(input);
+                            }
+                        }
+                    }
+                },
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    // the next piece comes in at the top of the loop
+                    if session.game.delay.is_some() {
+                        continue;
+                    }
+                    if let Some(bot) = self.bot.as_mut() {
+                        if planned.is_empty() {
+                            match bot.plan(&session.game) {
+                                Ok(Some(plan)) => {
+                                    planned.extend(plan.inputs);
+                                    target = Some(plan.piece);
+                                }
+                                Ok(None) => planned.push_back(Input::HardDrop),
+                                // the player takes over when the bot goes away
+                                Err(err) => {
+                                    self.status = Some(format!("The bot stopped: {}", err));
+                                    self.bot = None;
+                                    continue;
+                                }
+                            }
+                        }
+                        if let Some(input) = planned.pop_front() {
+                            session.play(input);
+                        }
+                    }
+                    // woken up early by the bot or to draw the animations
+                    if session.game.get_timeout() > 0 {
+                        continue;
+                    }
+                    if !session.game.is_over() {
+                        let pieces = session.game.stats.pieces;
+                        session.play(Input::Gravity);
+                        // the piece fell under the bot, find the way from where it is now
+                        if let (false, Some(piece)) = (planned.is_empty(), &target) {
+                            let route = (session.game.stats.pieces == pieces)
+                                .then(|| bot::route(&session.game, piece)).flatten();
+                            planned = route.unwrap_or_default().into();
+                        }
+                    }
+                },
+                Err(mpsc::RecvTimeoutError::Disconnected) =>
+                    return Err(io::Error::new(io::ErrorKind::BrokenPipe, "input thread died")),
+            }
+        }
+    }
+
+    // the board is hidden while paused so the pause can't be used to think ahead
+    fn pause(&mut self, game: &mut Tetris) -> io::Result<PauseChoice> {
+        const ITEMS: [&str; 4] = ["Resume", "Restart", "Settings", "Quit"];
+        let labels: Vec<String> = ITEMS.iter().map(|item| item.to_string()).collect();
+        let mut footer = vec![format!("{} - {}", game.mode, ui::format_time(game.elapsed_ms()))];
+        let mut menu = Menu::new();
+        game.pause();
+
+        let choice = loop {
+            let selected = Some(menu.selected);
+            self.terminal.draw(|f| ui::draw_menu(f, "Paused", &labels, selected, &footer))?;
+
+            let key = match self.next_key()? {
+                Some(key) => key,
+                None => continue,
+            };
+            if self.config.keys.action_for(key) == Some(Action::Pause) {
+                break PauseChoice::Resume;
+            }
+            match menu.handle_key(key, ITEMS.len()) {
+                MenuEvent::Chosen(0) | MenuEvent::Back => break PauseChoice::Resume,
+                MenuEvent::Chosen(1) => break PauseChoice::Restart,
+                MenuEvent::Chosen(2) => {
+                    self.edit_settings()?;
+                    footer.truncate(1);
+                    footer.push(self.save_config());
+                    footer.push("Rule changes apply from the next game".to_string());
+                }
+                MenuEvent::Chosen(_) => break PauseChoice::Quit,
+                _ => (),
+            }
+        };
+
+        game.resume();
+        Ok(choice)
+    }
+
+    // the game is over for good: keep the score and the recording
+    fn finish(&mut self, mut session: Session) -> io::Result<Screen> {
+        // stop the clock so the time shown behind the game over menu stays put
+        session.game.pause();
+        session.recording.finish(session.game.elapsed_ms());
+
+        // games played by the bot don't go on the high score tables
+        let place = match self.bot {
+            Some(_) => None,
+            None => self.high_scores.submit(&session.game),
+        };
+        let mut errors = Vec::new();
+        if place.is_some() {
+            let saved = highscores::default_path().map(|path| self.high_scores.save(&path));
+            if let Some(Err(err)) = saved {
+                errors.push(format!("Failed to save high scores: {}", err));
+            }
+        }
+        if let Some(Err(err)) = replay::autosave_path().filter(|path| path.exists())
+            .map(std::fs::remove_file) {
+            errors.push(format!("Failed to remove the saved game: {}", err));
+        }
+        let recorded = self.record_path.as_deref().map(|path| session.recording.save(path));
+        if let Some(Err(err)) = recorded {
+            errors.push(format!("Failed to save the replay: {}", err));
+        }
+        if !errors.is_empty() {
+            self.status = Some(errors.join("\n"));
+        }
+
+        Ok(Screen::GameOver(Box::new(session), place))
+    }
+
+    // quitting an unfinished game keeps it around for resuming
+    fn leave(&mut self, mut session: Session) -> io::Result<Screen> {
+        // stop the clock so the saved game is as long as it was played, and resumes there
+        session.game.pause();
+        session.recording.finish(session.game.elapsed_ms());
+        if let Some(Err(err)) = replay::autosave_path().map(|path| session.recording.save(&path)) {
+            self.status = Some(format!("Failed to save the game: {}", err));
+        }
+        Ok(Screen::Title)
+    }
+
+    // a signal to terminate that came through the pause menu still saves the game
+    fn save_before(&mut self, err: io::Error, session: Session) -> io::Result<Screen> {
+        if terminated(&err) {
+            self.leave(session)?;
+        }
+        Err(err)
+    }
+
+    fn edit_key_bindings(&mut self) -> io::Result<()> {
+        let mut editor = KeyBindingEditor::new();
+        loop {
+            let keymap: &KeyMap = &self.config.keys;
+            self.terminal.draw(|frame| editor.draw(keymap, frame.size(), frame))?;
+            let key = match self.next_key()? {
+                Some(key) => key,
+                None => continue,
+            };
+            match editor.handle_key(key, &mut self.config.keys) {
+                EditorOutcome::Continue => (),
+                EditorOutcome::Close => return Ok(()),
+            }
+        }
+    }
+
+    // returns a status line for the player
+    fn save_config(&self) -> String {
+        match self.config_path.as_deref() {
+            Some(path) => match self.config.save(path) {
+                Ok(()) => format!("Saved to {}", path.display()),
+                Err(err) => err.to_string(),
+            },
+            None => "Nowhere to save the config, use --config".to_string(),
+        }
+    }
+
+    // plays the recording back at its original pace, the quit key stops it
+    pub fn watch_replay(&mut self, replay: &Replay) -> io::Result<()> {
+        let mut game = replay.start();
+        let mut started = Instant::now();
+        let visuals = self.config.visuals.clone();
+        let mut animations = Animations::new();
+
+        for event in replay.events.iter() {
+            loop {
+                // the game's clock only moves with the inputs, between them it has to be
+                // moved on for the next piece to come in
+                if game.delay.is_some() {
+                    let elapsed = started.elapsed().as_millis() as u64;
+                    game.set_elapsed_ms(elapsed.min(event.at_ms).max(game.elapsed_ms()));
+                    game.update();
+                }
+                let now = Instant::now();
+                for event in game.take_events() {
+                    if visuals.animations {
+                        animations.start(event, now);
+                    }
+                }
+                let overlay = animations.overlay(now);
+                self.terminal.draw(|f| ui::draw_animated_game(f, &game, &visuals, &overlay))?;
+                let due = Duration::from_millis(event.at_ms);
+                let elapsed = started.elapsed();
+                if elapsed >= due {
+                    break;
+                }
+                let mut wait = due - elapsed;
+                if !animations.is_empty() {
+                    wait = wait.min(Duration::from_millis(animation::FRAME_MS));
+                }
+                match self.events.receiver.recv_timeout(wait) {
+                    Ok(Event::Key(key)) => {
+                        if self.config.keys.action_for(key) == Some(Action::Quit) {
+                            return Ok(());
+                        }
+                    }
+                    Ok(Event::Terminate) => return Ok(()),
+                    // the replay picks up where it was stopped
+                    Ok(Event::Suspend) => {
+                        let suspended = Instant::now();
+                        self.suspend()?;
+                        started += suspended.elapsed();
+                    }
+                    Ok(Event::Redraw) | Err(_) => (),
+                }
+            }
+            Replay::apply(&mut game, event);
+        }
+        game.set_elapsed_ms(replay.length_ms);
+
+        self.terminal.draw(|f| ui::draw_game(f, &game, &visuals))?;
+        ui::put_message_on_screen(&ui::summary(&game), &mut self.terminal);
+        std::thread::sleep(Duration::from_secs(2));
+        Ok(())
+    }
+}
@@ -0,0 +1,95 @@
+// a tiny Tetris Bot Protocol bot used to test the frontend: it never rotates or
+// holds and drops every piece in the column where it lands the lowest. With --stuck it
+// never answers a suggest and doesn't quit when told to.
+
+use std::collections::VecDeque;
+use std::io::{self, BufRead, Write};
+
+use tui_tetris::tbp::{BotMessage, FrontendMessage, Location, Move, Orientation, Spin};
+
+struct State {
+    // rows from the bottom up, true for filled cells
+    board: Vec<Vec<bool>>,
+    queue: VecDeque<char>,
+}
+
+impl State {
+    fn fits(&self, location: &Location) -> bool {
+        location.cells().is_some_and(|cells| cells.iter().all(|&(x, y)| {
+            x >= 0 && y >= 0 && (y as usize) < self.board.len()
+                && (x as usize) < self.board[y as usize].len()
+                && !self.board[y as usize][x as usize]
+        }))
+    }
+
+    fn suggest(&self) -> Option<Move> {
+        let piece = *self.queue.front()?;
+        let width = self.board.first().map_or(0, Vec::len) as i32;
+        let top = self.board.len() as i32 - 2;
+
+        (0..width).filter_map(|x| {
+            let mut location = Location { piece, orientation: Orientation::North, x, y: top };
+            if !self.fits(&location) {
+                return None;
+            }
+            while location.y > 0 && self.fits(&Location { y: location.y - 1, ..location.clone() }) {
+                location.y -= 1;
+            }
+            Some(location)
+        }).min_by_key(|location| location.y)
+          .map(|location| Move { location, spin: Spin::None })
+    }
+
+    fn play(&mut self, play: &Move) {
+        self.queue.pop_front();
+        for (x, y) in play.location.cells().into_iter().flatten() {
+            self.board[y as usize][x as usize] = true;
+        }
+        self.board.retain(|row| !row.iter().all(|&cell| cell));
+        let width = self.board.first().map_or(0, Vec::len);
+        while self.board.len() < tui_tetris::tbp::BOARD_ROWS {
+            self.board.push(vec![false; width]);
+        }
+    }
+}
+
+fn send(message: &BotMessage) -> io::Result<()> {
+    let mut stdout = io::stdout().lock();
+    serde_json::to_writer(&mut stdout, message)?;
+    stdout.write_all(b"\n")?;
+    stdout.flush()
+}
+
+fn main() -> io::Result<()> {
+    let stuck = std::env::args().any(|arg| arg == "--stuck");
+    send(&BotMessage::Info {
+        name: "mock".to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        author: "tui-tetris".to_string(),
+        features: Vec::new(),
+    })?;
+
+    let mut state = State { board: Vec::new(), queue: VecDeque::new() };
+    for line in io::stdin().lock().lines() {
+        match serde_json::from_str(&line?)? {
+            FrontendMessage::Rules { .. } => send(&BotMessage::Ready)?,
+            FrontendMessage::Start { queue, board, .. } => {
+                state = State {
+                    board: board.iter()
+                        .map(|row| row.iter().map(Option::is_some).collect()).collect(),
+                    queue: queue.into_iter().collect(),
+                };
+            }
+            FrontendMessage::Suggest if stuck => {}
+            FrontendMessage::Suggest => send(&BotMessage::Suggestion {
+                moves: state.suggest().into_iter().collect(),
+            })?,
+            FrontendMessage::Play { play } => state.play(&play),
+            FrontendMessage::NewPiece { piece } => state.queue.push_back(piece),
+            FrontendMessage::Stop => state.queue.clear(),
+            FrontendMessage::Quit if stuck => {}
+            FrontendMessage::Quit => break,
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use crate::pile::Pile;
+
+// scores the pile left behind by a placement, higher is better
+pub trait Evaluator {
+    fn evaluate(&self, pile: &Pile, cleared: usize) -> f64;
+}
+
+// a weighted sum of the usual board features, the weights multiply the raw
+// values so the penalties are negative
+#[derive(Clone, Copy, Debug)]
+pub struct Heuristic {
+    pub holes: f64,
+    pub bumpiness: f64,
+    pub aggregate_height: f64,
+    pub wells: f64,
+    pub lines: f64,
+}
+
+impl Default for Heuristic {
+    // weights found by Yiyuan Lee's genetic search, plus a small penalty for wells
+    fn default() -> Self {
+        Heuristic {
+            holes: -0.35663,
+            bumpiness: -0.184483,
+            aggregate_height: -0.510066,
+            wells: -0.1,
+            lines: 0.760666,
+        }
+    }
+}
+
+impl Evaluator for Heuristic {
+    fn evaluate(&self, pile: &Pile, cleared: usize) -> f64 {
+        let heights = column_heights(pile);
+        let bumpiness: usize = heights.windows(2)
+            .map(|pair| (pair[0] as isize - pair[1] as isize).unsigned_abs()).sum();
+
+        self.holes * holes(pile) as f64
+            + self.bumpiness * bumpiness as f64
+            + self.aggregate_height * heights.iter().sum::<usize>() as f64
+            + self.wells * wells(&heights, pile.row_count) as f64
+            + self.lines * cleared as f64
+    }
+}
+
+// how high the stack is in each column, counted from the floor
+pub fn column_heights(pile: &Pile) -> Vec<usize> {
+    let mut heights = vec![0; pile.col_count];
+    let mut seen = 0u64;
+    for (i, row) in pile.rows.iter().enumerate() {
+        let mut tops = row & !seen;
+        seen |= row;
+        while tops != 0 {
+            heights[tops.trailing_zeros() as usize] = pile.row_count - i;
+            tops &= tops - 1;
+        }
+    }
+    heights
+}
+
+// empty cells with something above them in the same column
+pub fn holes(pile: &Pile) -> usize {
+    let mut above = 0u64;
+    pile.rows.iter().map(|row| {
+        let covered = (above & !row).count_ones() as usize;
+        above |= row;
+        covered
+    }).sum()
+}
+
+// the summed depth of columns that are lower than both of their neighbours,
+// the walls count as being as high as the board
+pub fn wells(heights: &[usize], wall: usize) -> usize {
+    (0..heights.len()).map(|j| {
+        let left = if j == 0 { wall } else { heights[j - 1] };
+        let right = heights.get(j + 1).copied().unwrap_or(wall);
+        left.min(right).saturating_sub(heights[j])
+    }).sum()
+}
@@ -0,0 +1,77 @@
+use std::io;
+
+use crate::piece::Piece;
+use crate::tetris::{finesse, movegen, Input, Tetris};
+
+pub mod evaluator;
+pub use evaluator::{Evaluator, Heuristic};
+
+// what the bot decided to do with the current piece
+pub struct Plan {
+    // the inputs in order, starting with the hold and ending with the hard drop
+    pub inputs: Vec<Input>,
+    // where the piece ends up
+    pub piece: Piece,
+}
+
+// whatever decides the moves of each piece: the built-in bot or an external one
+pub trait Player {
+    // None when there's nowhere to put the piece
+    fn plan(&mut self, game: &Tetris) -> io::Result<Option<Plan>>;
+}
+
+pub struct Bot {
+    evaluator: Box<dyn Evaluator>,
+}
+
+impl Default for Bot {
+    fn default() -> Self {
+        Bot::new(Box::new(Heuristic::default()))
+    }
+}
+
+impl Bot {
+    pub fn new(evaluator: Box<dyn Evaluator>) -> Self {
+        Bot { evaluator }
+    }
+
+    // the best placement of either the current or the held piece
+    pub fn plan(&self, game: &Tetris) -> Option<Plan> {
+        candidates(game).into_iter().map(|plan| {
+            let mut pile = game.pile.clone();
+            pile.add(&plan.piece);
+            let cleared = pile.cleanup_full_lines();
+            (self.evaluator.evaluate(&pile, cleared), plan)
+        }).max_by(|a, b| a.0.total_cmp(&b.0)).map(|(_, plan)| plan)
+    }
+}
+
+impl Player for Bot {
+    fn plan(&mut self, game: &Tetris) -> io::Result<Option<Plan>> {
+        Ok(Bot::plan(self, game))
+    }
+}
+
+// every placement of the current piece and of the one that would come out of hold,
+// tucks and spins included
+pub fn candidates(game: &Tetris) -> Vec<Plan> {
+    let mut result: Vec<Plan> = movegen::moves(game, &game.current_piece).into_iter()
+        .map(|found| Plan { inputs: found.inputs, piece: found.piece }).collect();
+    if let Some(held) = game.held_piece() {
+        result.extend(movegen::moves(game, &held).into_iter().map(|found| {
+            let mut inputs = vec![Input::Hold];
+            inputs.extend(found.inputs);
+            Plan { inputs, piece: found.piece }
+        }));
+    }
+    result
+}
+
+// the inputs that take the piece from where it is now to where the plan put it
+pub fn route(game: &Tetris, target: &Piece) -> Option<Vec<Input>> {
+    let placement = finesse::placement(target);
+    candidates(game).into_iter()
+        .find(|plan| plan.piece.piece_type == target.piece_type
+              && finesse::placement(&plan.piece) == placement)
+        .map(|plan| plan.inputs)
+}
@@ -0,0 +1,138 @@
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+use std::path::PathBuf;
+
+use crate::config::Rules;
+use crate::tetris::{Mode, RotationSystem};
+
+#[derive(Parser)]
+#[command(name = "tui-tetris", version, about = "Simple TUI tetris")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Start a game in this mode right away instead of showing the menu
+    #[arg(long, value_enum)]
+    pub mode: Option<Mode>,
+
+    /// Seed for the piece sequence, random when not given
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    #[command(flatten)]
+    pub overrides: Overrides,
+
+    /// Play back a recorded game
+    #[arg(long, value_name = "FILE", conflicts_with = "resume")]
+    pub replay: Option<PathBuf>,
+
+    /// Continue the game that was left unfinished last time
+    #[arg(long)]
+    pub resume: bool,
+
+    /// Write a replay of the game to this file
+    #[arg(long, value_name = "FILE")]
+    pub record: Option<PathBuf>,
+
+    /// Use this config file instead of the default one
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Let the built-in bot play instead of the keyboard
+    #[arg(long)]
+    pub bot: bool,
+
+    /// Let an external bot speaking the Tetris Bot Protocol play, for example
+    /// "cold-clear --tbp". Standard bots expect a board 10 cells wide
+    #[arg(long, value_name = "COMMAND", conflicts_with = "bot")]
+    pub tbp: Option<String>,
+
+    /// Play a recorded game through without a terminal and print its final score, to check
+    /// that it still plays out the same
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["replay", "resume"])]
+    pub verify_replay: Option<PathBuf>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Play many games with a bot, without a terminal, and print statistics about them
+    Simulate(SimulateArgs),
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Format {
+    Csv,
+    Json,
+}
+
+#[derive(Args)]
+pub struct SimulateArgs {
+    /// Number of games to play
+    #[arg(long, default_value_t = 100)]
+    pub games: usize,
+
+    /// Mode of every game
+    #[arg(long, value_enum, default_value_t = Mode::Marathon)]
+    pub mode: Mode,
+
+    /// Seed of the first game, the others count up from it
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+
+    /// Games running at the same time, one per CPU core by default
+    #[arg(long)]
+    pub threads: Option<usize>,
+
+    /// Stop a game after this many pieces
+    #[arg(long, default_value_t = 1000)]
+    pub max_pieces: usize,
+
+    /// Weights of the built-in bot: holes, bumpiness, aggregate height, wells and lines
+    #[arg(long, value_name = "H,B,A,W,L", value_delimiter = ',', allow_hyphen_values = true)]
+    pub weights: Option<Vec<f64>>,
+
+    /// Use an external Tetris Bot Protocol bot, one process per thread
+    #[arg(long, value_name = "COMMAND", conflicts_with = "weights")]
+    pub tbp: Option<String>,
+
+    /// Print one line per game instead of the summary
+    #[arg(long)]
+    pub per_game: bool,
+
+    #[arg(long, value_enum, default_value_t = Format::Csv)]
+    pub format: Format,
+
+    /// Write the output to this file instead of stdout
+    #[arg(long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+// rules given on the command line, for this run only. They are never saved to the config file.
+#[derive(Args, Clone, Default)]
+pub struct Overrides {
+    /// Board width in cells, overrides the config file
+    #[arg(long)]
+    pub width: Option<usize>,
+
+    /// Board height in cells, overrides the config file
+    #[arg(long)]
+    pub height: Option<usize>,
+
+    /// Rotation system, overrides the config file
+    #[arg(long, value_enum)]
+    pub rotation: Option<RotationSystem>,
+}
+
+impl Overrides {
+    pub fn apply(&self, rules: &mut Rules) {
+        if let Some(width) = self.width {
+            rules.width = width;
+        }
+        if let Some(height) = self.height {
+            rules.height = height;
+        }
+        if let Some(rotation) = self.rotation {
+            rules.rotation = rotation;
+        }
+    }
+}
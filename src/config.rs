@@ -1,9 +1,291 @@
-pub const END_PLAYING_SCREEN_X : usize = 74;
-pub const END_SCREEN_Y : usize = 54;
+use serde::{Deserialize, Serialize};
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::controls::{Action, KeyMap};
+use crate::tetris::RotationSystem;
+use crate::input::key_format;
+use crate::theme::ColorDepth;
 
 pub const BLOCK_HEIGHT : usize = 2;
 pub const BLOCK_WIDTH : usize = BLOCK_HEIGHT * 2;
-pub const LEFT_THRESHOLD : usize = 0;
-pub const RIGHT_THRESHOLD : usize = END_PLAYING_SCREEN_X / BLOCK_WIDTH;
-pub const BOTTOM_THRESHOLD : usize = END_SCREEN_Y / BLOCK_HEIGHT;
-pub const INITIAL_TICK_TIME_MS : usize = 1000;
+
+pub const DEFAULT_WIDTH : usize = 18;
+pub const DEFAULT_HEIGHT : usize = 27;
+pub const DEFAULT_LOCK_DELAY_MS : usize = 1000;
+pub const DEFAULT_PREVIEW_COUNT : usize = 1;
+
+const MIN_WIDTH : usize = 4;
+const MAX_WIDTH : usize = 40;
+const MIN_HEIGHT : usize = 4;
+const MAX_HEIGHT : usize = 60;
+const MAX_LOCK_DELAY_MS : usize = 10_000;
+const MAX_SPAWN_DELAY_MS : usize = 2000;
+const MAX_PREVIEW_COUNT : usize = 6;
+const MAX_HANDLING_MS : usize = 1000;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GravityPreset {
+    // the original speed curve: 1s per row, getting 20ms faster per cleared line
+    Classic,
+    Relaxed,
+    Fast,
+}
+
+impl GravityPreset {
+    pub const ALL: [GravityPreset; 3] =
+        [GravityPreset::Classic, GravityPreset::Relaxed, GravityPreset::Fast];
+
+    pub fn initial_tick_ms(&self) -> usize {
+        match self {
+            GravityPreset::Classic => 1000,
+            GravityPreset::Relaxed => 1500,
+            GravityPreset::Fast => 500,
+        }
+    }
+
+    // how many ms each cleared line takes off the tick time
+    pub fn quickening_ms(&self) -> usize {
+        match self {
+            GravityPreset::Classic => 20,
+            GravityPreset::Relaxed => 10,
+            GravityPreset::Fast => 25,
+        }
+    }
+
+    pub fn speed_cap_ms(&self) -> usize {
+        match self {
+            GravityPreset::Classic => 20,
+            GravityPreset::Relaxed => 100,
+            GravityPreset::Fast => 20,
+        }
+    }
+}
+
+// the palettes are in theme.rs
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Theme {
+    // the terminal's own colors
+    Classic,
+    // the colors of the guideline games
+    Guideline,
+    Monochrome,
+    HighContrast,
+    // safe for deuteranopia and protanopia
+    Colorblind,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 5] = [Theme::Classic, Theme::Guideline, Theme::Monochrome,
+                                 Theme::HighContrast, Theme::Colorblind];
+}
+
+// how the cells of the board are drawn
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Renderer {
+    // bordered blocks of BLOCK_WIDTH x BLOCK_HEIGHT
+    Blocks,
+    // two full block characters per cell
+    Compact,
+    // one character per cell, two rows per line, small enough for 80x24
+    HalfBlock,
+}
+
+impl Renderer {
+    pub const ALL: [Renderer; 3] = [Renderer::Blocks, Renderer::Compact, Renderer::HalfBlock];
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Rules {
+    pub width: usize,
+    pub height: usize,
+    pub gravity: GravityPreset,
+    pub lock_delay_ms: usize,
+    // how long cleared lines stay on the board before the rows above fall down
+    pub line_clear_delay_ms: usize,
+    // entry delay (ARE), between a piece locking or the lines clearing and the next piece
+    pub entry_delay_ms: usize,
+    pub preview_count: usize,
+    pub rotation: RotationSystem,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            width: DEFAULT_WIDTH,
+            height: DEFAULT_HEIGHT,
+            gravity: GravityPreset::Classic,
+            lock_delay_ms: DEFAULT_LOCK_DELAY_MS,
+            line_clear_delay_ms: 0,
+            entry_delay_ms: 0,
+            preview_count: DEFAULT_PREVIEW_COUNT,
+            rotation: RotationSystem::Classic,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Handling {
+    // delayed auto shift - how long a move key has to be held before it repeats
+    pub das_ms: usize,
+    // auto repeat rate - time between repeated moves, 0 means straight to the wall
+    pub arr_ms: usize,
+}
+
+impl Default for Handling {
+    fn default() -> Self {
+        Handling { das_ms: 150, arr_ms: 30 }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Visuals {
+    pub theme: Theme,
+    pub renderer: Renderer,
+    // the colors of the theme are brought down to this
+    pub color_depth: ColorDepth,
+    // pieces are drawn with a pattern of their own too, except by the half block renderer
+    pub patterns: bool,
+    // cleared lines, locks and level ups are animated, without holding up the game
+    pub animations: bool,
+}
+
+impl Default for Visuals {
+    fn default() -> Self {
+        Visuals { theme: Theme::Classic, renderer: Renderer::Blocks,
+                  color_depth: ColorDepth::Auto, patterns: false, animations: true }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub rules: Rules,
+    pub handling: Handling,
+    pub visuals: Visuals,
+    pub keys: KeyMap,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, std::io::Error),
+    Serialize(toml::ser::Error),
+    Parse(PathBuf, toml::de::Error),
+    // a value that parsed fine but makes no sense, keyed by its dotted path
+    Invalid { key: String, message: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, err) =>
+                write!(f, "failed to access config {}: {}", path.display(), err),
+            ConfigError::Serialize(err) =>
+                write!(f, "failed to serialize config: {}", err),
+            ConfigError::Parse(path, err) =>
+                write!(f, "invalid config {}: {}", path.display(), err),
+            ConfigError::Invalid { key, message } =>
+                write!(f, "invalid config value for `{}`: {}", key, message),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+fn check_range(key: &str, value: usize, min: usize, max: usize) -> Result<(), ConfigError> {
+    if value < min || value > max {
+        return Err(ConfigError::Invalid {
+            key: key.to_string(),
+            message: format!("must be between {} and {} (got {})", min, max, value),
+        });
+    }
+    Ok(())
+}
+
+impl Config {
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        check_range("rules.width", self.rules.width, MIN_WIDTH, MAX_WIDTH)?;
+        check_range("rules.height", self.rules.height, MIN_HEIGHT, MAX_HEIGHT)?;
+        check_range("rules.lock_delay_ms", self.rules.lock_delay_ms, 0, MAX_LOCK_DELAY_MS)?;
+        check_range("rules.line_clear_delay_ms", self.rules.line_clear_delay_ms,
+                    0, MAX_SPAWN_DELAY_MS)?;
+        check_range("rules.entry_delay_ms", self.rules.entry_delay_ms, 0, MAX_SPAWN_DELAY_MS)?;
+        check_range("rules.preview_count", self.rules.preview_count, 1, MAX_PREVIEW_COUNT)?;
+        check_range("handling.das_ms", self.handling.das_ms, 0, MAX_HANDLING_MS)?;
+        check_range("handling.arr_ms", self.handling.arr_ms, 0, MAX_HANDLING_MS)?;
+
+        for action in Action::ALL.iter() {
+            if self.keys.keys_for(*action).is_empty() {
+                return Err(ConfigError::Invalid {
+                    key: format!("keys.{}", action.config_name()),
+                    message: "needs at least one key".to_string(),
+                });
+            }
+        }
+        if let Some((action, conflict)) = self.keys.find_conflict() {
+            return Err(ConfigError::Invalid {
+                key: format!("keys.{}", action.config_name()),
+                message: format!("`{}` is already bound to `keys.{}`",
+                                 key_format::to_string(&conflict.key),
+                                 conflict.action.config_name()),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn from_toml(path: &Path, contents: &str) -> Result<Self, ConfigError> {
+        let config: Config = toml::from_str(contents)
+            .map_err(|err| ConfigError::Parse(path.to_path_buf(), err))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    // an explicitly requested file has to exist, the default one is optional
+    pub fn load(explicit: Option<&Path>) -> Result<Self, ConfigError> {
+        let path = match explicit {
+            Some(path) => path.to_path_buf(),
+            None => match default_path() {
+                Some(path) if path.exists() => path,
+                _ => return Ok(Config::default()),
+            },
+        };
+
+        let contents = std::fs::read_to_string(&path)
+            .map_err(|err| ConfigError::Io(path.clone(), err))?;
+        Self::from_toml(&path, &contents)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), ConfigError> {
+        let contents = toml::to_string_pretty(self).map_err(ConfigError::Serialize)?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|err| ConfigError::Io(dir.to_path_buf(), err))?;
+        }
+        std::fs::write(path, contents).map_err(|err| ConfigError::Io(path.to_path_buf(), err))
+    }
+}
+
+// $XDG_DATA_HOME/tui-tetris, falling back to ~/.local/share
+pub fn data_dir() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_DATA_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".local").join("share"),
+    };
+    Some(base.join("tui-tetris"))
+}
+
+// $XDG_CONFIG_HOME/tui-tetris/config.toml, falling back to ~/.config
+pub fn default_path() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join("tui-tetris").join("config.toml"))
+}
@@ -0,0 +1,221 @@
+use serde::de::Deserializer;
+use serde::ser::{SerializeSeq, Serializer};
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::input::{key_format, Key};
+use crate::tetris::Input;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    SoftDrop,
+    HardDrop,
+    RotateCw,
+    RotateCcw,
+    #[serde(rename = "rotate_180")]
+    Rotate180,
+    Hold,
+    Pause,
+    Restart,
+    Quit,
+}
+
+impl Action {
+    pub const ALL: [Action; 11] = [
+        Action::MoveLeft, Action::MoveRight, Action::SoftDrop, Action::HardDrop,
+        Action::RotateCw, Action::RotateCcw, Action::Rotate180, Action::Hold,
+        Action::Pause, Action::Restart, Action::Quit,
+    ];
+
+    fn default_keys(&self) -> Vec<Key> {
+        match self {
+            Action::MoveLeft => vec![Key::Left],
+            Action::MoveRight => vec![Key::Right],
+            Action::SoftDrop => vec![Key::Down],
+            Action::HardDrop => vec![Key::Char(' ')],
+            Action::RotateCw => vec![Key::Char('d')],
+            Action::RotateCcw => vec![Key::Char('a')],
+            Action::Rotate180 => vec![Key::Char('w')],
+            Action::Hold => vec![Key::Char('s')],
+            Action::Pause => vec![Key::Char('p')],
+            Action::Restart => vec![Key::Char('r')],
+            Action::Quit => vec![Key::Char('q')],
+        }
+    }
+
+    // the engine input behind this action, if it is not a UI only action
+    pub fn input(&self) -> Option<Input> {
+        match self {
+            Action::MoveLeft => Some(Input::MoveLeft),
+            Action::MoveRight => Some(Input::MoveRight),
+            Action::SoftDrop => Some(Input::SoftDrop),
+            Action::HardDrop => Some(Input::HardDrop),
+            Action::RotateCw => Some(Input::RotateCw),
+            Action::RotateCcw => Some(Input::RotateCcw),
+            Action::Rotate180 => Some(Input::Rotate180),
+            Action::Hold => Some(Input::Hold),
+            Action::Pause | Action::Restart | Action::Quit => None,
+        }
+    }
+
+    // the name used for this action in the config file
+    pub fn config_name(&self) -> &'static str {
+        match self {
+            Action::MoveLeft => "move_left",
+            Action::MoveRight => "move_right",
+            Action::SoftDrop => "soft_drop",
+            Action::HardDrop => "hard_drop",
+            Action::RotateCw => "rotate_cw",
+            Action::RotateCcw => "rotate_ccw",
+            Action::Rotate180 => "rotate_180",
+            Action::Hold => "hold",
+            Action::Pause => "pause",
+            Action::Restart => "restart",
+            Action::Quit => "quit",
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Action::MoveLeft => "Move left",
+            Action::MoveRight => "Move right",
+            Action::SoftDrop => "Soft drop",
+            Action::HardDrop => "Hard drop",
+            Action::RotateCw => "Rotate clockwise",
+            Action::RotateCcw => "Rotate counterclockwise",
+            Action::Rotate180 => "Rotate 180",
+            Action::Hold => "Hold",
+            Action::Pause => "Pause",
+            Action::Restart => "Restart",
+            Action::Quit => "Quit",
+        })
+    }
+}
+
+// a key that is already taken by another action
+pub struct Conflict {
+    pub key: Key,
+    pub action: Action,
+}
+
+// maps every action to the keys that trigger it, a key triggers at most one action
+#[derive(Clone, Debug)]
+pub struct KeyMap {
+    bindings: BTreeMap<Action, Vec<Key>>,
+}
+
+impl KeyMap {
+    pub fn action_for(&self, key: Key) -> Option<Action> {
+        self.bindings.iter()
+            .find(|(_, keys)| keys.contains(&key))
+            .map(|(action, _)| *action)
+    }
+
+    pub fn keys_for(&self, action: Action) -> &[Key] {
+        self.bindings.get(&action).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn bind(&mut self, action: Action, key: Key) -> Result<(), Conflict> {
+        match self.action_for(key) {
+            Some(other) if other == action => Ok(()),
+            Some(other) => Err(Conflict { key, action: other }),
+            None => {
+                self.bindings.entry(action).or_default().push(key);
+                Ok(())
+            }
+        }
+    }
+
+    // removes the most recently added key of the action, keeping at least one
+    pub fn unbind_last(&mut self, action: Action) -> bool {
+        match self.bindings.get_mut(&action) {
+            Some(keys) if keys.len() > 1 => {
+                keys.pop();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // the first key that is bound to more than one action
+    pub fn find_conflict(&self) -> Option<(Action, Conflict)> {
+        let mut seen: Vec<(Key, Action)> = Vec::new();
+        for (action, keys) in self.bindings.iter() {
+            for key in keys {
+                if let Some((_, other)) = seen.iter().find(|(k, _)| k == key) {
+                    return Some((*action, Conflict { key: *key, action: *other }));
+                }
+                seen.push((*key, *action));
+            }
+        }
+        None
+    }
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        KeyMap {
+            bindings: Action::ALL.iter()
+                .map(|action| (*action, action.default_keys()))
+                .collect(),
+        }
+    }
+}
+
+// lets the config hold either `hold = "s"` or `hold = ["s", "c"]`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum KeyNames {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl<'de> Deserialize<'de> for KeyMap {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let overrides = BTreeMap::<Action, KeyNames>::deserialize(deserializer)?;
+        let mut keymap = KeyMap::default();
+
+        for (action, names) in overrides {
+            let names = match names {
+                KeyNames::One(name) => vec![name],
+                KeyNames::Many(names) => names,
+            };
+            let mut keys = Vec::new();
+            for name in names.iter() {
+                let key = key_format::parse(name).map_err(serde::de::Error::custom)?;
+                // a key listed twice for the same action is bound once, it is no conflict
+                if !keys.contains(&key) {
+                    keys.push(key);
+                }
+            }
+            keymap.bindings.insert(action, keys);
+        }
+        Ok(keymap)
+    }
+}
+
+struct KeyList<'a>(&'a [Key]);
+
+impl Serialize for KeyList<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
+        for key in self.0 {
+            seq.serialize_element(&key_format::to_string(key))?;
+        }
+        seq.end()
+    }
+}
+
+impl Serialize for KeyMap {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.bindings.iter()
+            .map(|(action, keys)| (action, KeyList(keys))))
+    }
+}
@@ -0,0 +1,126 @@
+// crash reporting. While the game runs the terminal is in raw mode on the alternate
+// screen, so a panic message printed right away is lost, and the terminal is only
+// restored as the panic unwinds through the backend. The panic hook writes a crash
+// report instead, and the guard prints it once the terminal is back to normal.
+
+use std::backtrace::Backtrace;
+use std::fmt::Write as _;
+use std::io::Write;
+use std::panic::{self, PanicHookInfo};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config;
+use crate::replay::Replay;
+use crate::tetris::Input;
+
+// how many of the last inputs go into the report, the replay has them all
+const RECENT_INPUTS: usize = 30;
+
+// the game in play, recorded again here so the hook can get to it
+static GAME: Mutex<Option<Replay>> = Mutex::new(None);
+// what the guard prints when it goes away
+static MESSAGE: Mutex<Option<String>> = Mutex::new(None);
+
+// prints the message of a crash, if there was one, when dropped. It has to be
+// dropped after the terminal.
+pub struct Guard {
+    _private: (),
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        let message = MESSAGE.lock().unwrap_or_else(|err| err.into_inner()).take();
+        if let Some(message) = message {
+            // the way back to the main screen can still be sitting in the buffer
+            let _ = std::io::stdout().flush();
+            eprintln!("{}", message);
+        }
+    }
+}
+
+// replaces the panic hook for as long as the game has the terminal
+pub fn install() -> Guard {
+    panic::set_hook(Box::new(|info| {
+        let message = report(info);
+        *MESSAGE.lock().unwrap_or_else(|err| err.into_inner()) = Some(message);
+    }));
+    Guard { _private: () }
+}
+
+pub fn start_game(recording: &Replay) {
+    *GAME.lock().unwrap_or_else(|err| err.into_inner()) = Some(recording.clone());
+}
+
+pub fn record(at_ms: u64, input: Input, repeat: bool) {
+    if let Some(game) = GAME.lock().unwrap_or_else(|err| err.into_inner()).as_mut() {
+        game.record(at_ms, input, repeat);
+    }
+}
+
+fn panic_message(info: &PanicHookInfo) -> String {
+    let payload = info.payload();
+    let text = payload.downcast_ref::<&str>().copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown error");
+    match info.location() {
+        Some(location) => format!("{} (at {})", text, location),
+        None => text.to_string(),
+    }
+}
+
+fn input_name(input: Input) -> String {
+    serde_json::to_value(input).ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_else(|| format!("{:?}", input))
+}
+
+// writes the crash files and returns what to tell the player
+fn report(info: &PanicHookInfo) -> String {
+    let message = panic_message(info);
+    let thread = std::thread::current();
+    let mut text = format!("tui-tetris {} crashed in thread {}: {}\n\n",
+                           env!("CARGO_PKG_VERSION"), thread.name().unwrap_or("unnamed"),
+                           message);
+
+    // the hook can run while the panicking thread holds the lock
+    let game = GAME.try_lock().ok().and_then(|game| game.clone());
+    if let Some(game) = &game {
+        let _ = writeln!(text, "seed: {}\nmode: {}\ngame time: {}ms",
+                         game.seed, game.mode, game.length_ms);
+        let _ = writeln!(text, "\n[rules]\n{}", toml::to_string(&game.rules).unwrap_or_default());
+        let _ = writeln!(text, "last inputs (game time in ms, input):");
+        let skipped = game.events.len().saturating_sub(RECENT_INPUTS);
+        for event in &game.events[skipped..] {
+            let _ = writeln!(text, "{:>10} {}{}", event.at_ms, input_name(event.input),
+                             if event.repeat { " (held)" } else { "" });
+        }
+        text.push('\n');
+    }
+    let _ = writeln!(text, "backtrace:\n{}", Backtrace::force_capture());
+
+    let stamp = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs());
+    let base = config::data_dir().unwrap_or_else(std::env::temp_dir);
+    let report_path = base.join(format!("crash-{}.txt", stamp));
+    let written = std::fs::create_dir_all(&base)
+        .and_then(|_| std::fs::write(&report_path, &text));
+
+    let mut message = format!("tui-tetris crashed: {}\n", message);
+    match written {
+        Ok(()) => {
+            let _ = writeln!(message, "The crash report is in {}", report_path.display());
+        }
+        Err(err) => {
+            let _ = writeln!(message, "Failed to write the crash report: {}\n\n{}", err, text);
+        }
+    }
+    if let Some(game) = game {
+        let replay_path: PathBuf = base.join(format!("crash-{}.json", stamp));
+        if game.save(&replay_path).is_ok() {
+            let _ = writeln!(message, "The game up to the crash was seed {}, watch it with \
+                                       --replay {}", game.seed, replay_path.display());
+        }
+    }
+    message
+}
@@ -0,0 +1,155 @@
+use std::fmt;
+
+use crate::bot::{self, Plan};
+use crate::config::Rules;
+use crate::piece::PieceType;
+use crate::tetris::{Input, Mode, Outcome, Tetris};
+
+// the game clock moves this much with every input, there is no real time involved
+pub const STEP_MS: u64 = 50;
+
+// what an agent can do on a step
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+    // a single input, like a key press
+    Input(Input),
+    // an index into `Env::placements`, all the inputs to lock the piece there
+    Place(usize),
+}
+
+// how the reward of a step is made up, every part is multiplied by its weight
+#[derive(Clone, Copy, Debug)]
+pub struct Rewards {
+    pub lines: f64,
+    pub score: f64,
+    // for every step the game goes on
+    pub survival: f64,
+    // once, when the stack reaches the top
+    pub top_out: f64,
+}
+
+impl Default for Rewards {
+    fn default() -> Self {
+        Rewards { lines: 1.0, score: 0.0, survival: 0.0, top_out: 0.0 }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Observation {
+    pub width: usize,
+    pub height: usize,
+    // row by row from the top, true for filled cells
+    pub board: Vec<bool>,
+    pub current: PieceType,
+    // (row, column) of the cells of the current piece
+    pub current_cells: [(usize, usize); 4],
+    pub queue: Vec<PieceType>,
+    pub hold: PieceType,
+    // hold can be used only once per piece
+    pub can_hold: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct Step {
+    pub observation: Observation,
+    pub reward: f64,
+    pub done: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct InvalidAction(pub usize);
+
+impl fmt::Display for InvalidAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "there is no placement {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidAction {}
+
+// a step/reset interface for training agents on the same engine the game uses.
+// Everything follows from the seed given to reset, the same actions always
+// give the same observations and rewards.
+pub struct Env {
+    pub rules: Rules,
+    pub mode: Mode,
+    pub rewards: Rewards,
+    game: Tetris,
+    // placements of the current piece, worked out when first asked for
+    placements: Option<Vec<Plan>>,
+}
+
+impl Env {
+    pub fn new(rules: Rules, mode: Mode, rewards: Rewards) -> Self {
+        let game = Self::new_game(&rules, mode, 0);
+        Env { rules, mode, rewards, game, placements: None }
+    }
+
+    fn new_game(rules: &Rules, mode: Mode, seed: u64) -> Tetris {
+        let mut game = Tetris::new(rules, mode, seed);
+        game.set_elapsed_ms(0);
+        game.judge_finesse = false;
+        game
+    }
+
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        self.game = Self::new_game(&self.rules, self.mode, seed);
+        self.placements = None;
+        self.observation()
+    }
+
+    pub fn step(&mut self, action: Action) -> Result<Step, InvalidAction> {
+        let inputs = match action {
+            Action::Input(input) => vec![input],
+            Action::Place(idx) => self.placements().get(idx)
+                .ok_or(InvalidAction(idx))?.inputs.clone(),
+        };
+        let (lines, score) = (self.game.lines, self.game.score);
+        let was_over = self.game.is_over();
+
+        for input in inputs {
+            if self.game.is_over() {
+                break;
+            }
+            self.game.set_elapsed_ms(self.game.elapsed_ms() + STEP_MS);
+            self.game.apply(input);
+        }
+        // the agent never sees the board between two pieces
+        self.game.skip_delays();
+        self.placements = None;
+
+        let topped_out = self.game.outcome() == Some(Outcome::ToppedOut);
+        let reward = if was_over {
+            0.0
+        } else {
+            self.rewards.lines * (self.game.lines - lines) as f64
+                + self.rewards.score * (self.game.score - score) as f64
+                + if topped_out { self.rewards.top_out } else { self.rewards.survival }
+        };
+        Ok(Step { observation: self.observation(), reward, done: self.game.is_over() })
+    }
+
+    // every placement of the current and the held piece, in the same order for the same state
+    pub fn placements(&mut self) -> &[Plan] {
+        let game = &self.game;
+        self.placements.get_or_insert_with(|| bot::candidates(game))
+    }
+
+    pub fn observation(&self) -> Observation {
+        let game = &self.game;
+        Observation {
+            width: game.width(),
+            height: game.height(),
+            board: game.pile.to_vec(),
+            current: game.current_piece.piece_type,
+            current_cells: game.current_piece.get_positions(),
+            queue: game.next_pieces.iter().map(|piece| piece.piece_type).collect(),
+            hold: game.spare_piece.piece_type,
+            can_hold: !game.spare_used,
+        }
+    }
+
+    pub fn game(&self) -> &Tetris {
+        &self.game
+    }
+}
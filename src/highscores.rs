@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::config;
+use crate::tetris::{Mode, Outcome, Tetris};
+
+const MAX_ENTRIES: usize = 10;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub score: usize,
+    pub lines: usize,
+    pub time_ms: u64,
+    pub seed: u64,
+}
+
+impl Entry {
+    pub fn from_game(game: &Tetris) -> Self {
+        Entry { score: game.score, lines: game.lines, time_ms: game.elapsed_ms(), seed: game.seed }
+    }
+
+    // sprint is a race against the clock, everything else is about the score
+    fn is_better_than(&self, other: &Entry, mode: Mode) -> bool {
+        match mode {
+            Mode::Sprint => self.time_ms < other.time_ms,
+            Mode::Marathon | Mode::Ultra | Mode::Trainer => self.score > other.score,
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct HighScores {
+    tables: BTreeMap<Mode, Vec<Entry>>,
+}
+
+impl HighScores {
+    pub fn entries(&self, mode: Mode) -> &[Entry] {
+        self.tables.get(&mode).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    // returns the place the game got in its table, if it made it there at all
+    pub fn submit(&mut self, game: &Tetris) -> Option<usize> {
+        if game.mode == Mode::Sprint && game.outcome() != Some(Outcome::Completed) {
+            return None;
+        }
+
+        let entry = Entry::from_game(game);
+        let table = self.tables.entry(game.mode).or_default();
+        let place = table.iter()
+            .position(|other| entry.is_better_than(other, game.mode))
+            .unwrap_or(table.len());
+        if place >= MAX_ENTRIES {
+            return None;
+        }
+
+        table.insert(place, entry);
+        table.truncate(MAX_ENTRIES);
+        Some(place)
+    }
+
+    // a missing file just means nobody has played yet
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match std::fs::File::open(path) {
+            Ok(file) => Ok(serde_json::from_reader(io::BufReader::new(file))?),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(HighScores::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let file = std::fs::File::create(path)?;
+        Ok(serde_json::to_writer_pretty(io::BufWriter::new(file), self)?)
+    }
+}
+
+pub fn default_path() -> Option<PathBuf> {
+    config::data_dir().map(|dir| dir.join("highscores.json"))
+}
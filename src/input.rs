@@ -0,0 +1,285 @@
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config::Handling;
+use crate::controls::Action;
+use crate::terminal;
+
+// a key press, the same whichever terminal library reads it. Enter and Tab come
+// in as the characters '\n' and '\t'.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Key {
+    Char(char),
+    Alt(char),
+    Ctrl(char),
+    F(u8),
+    Left,
+    Right,
+    Up,
+    Down,
+    Backspace,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    BackTab,
+    Delete,
+    Insert,
+    Esc,
+}
+
+// what the game waits on: the keys, and the signals it reacts to
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Event {
+    Key(Key),
+    // the terminal was resized or handed back after a suspend, draw everything again
+    Redraw,
+    // Ctrl-Z, or SIGTSTP from outside
+    Suspend,
+    // SIGTERM or SIGHUP, save what can be saved and quit
+    Terminate,
+}
+
+pub struct Events {
+    pub receiver: mpsc::Receiver<Event>
+}
+
+impl Events {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        // just spawn a thread that will listen for key presses
+        let keys = sender.clone();
+        thread::spawn(move || {
+            for key in terminal::keys() {
+                // raw mode keeps the terminal from turning Ctrl-Z into SIGTSTP itself
+                let event = match key {
+                    Key::Ctrl('z') if cfg!(unix) => Event::Suspend,
+                    key => Event::Key(key),
+                };
+                if let Err(err) = keys.send(event) {
+                    eprintln!("Whops {}", err);
+                }
+            }
+        });
+        #[cfg(unix)]
+        listen_for_signals(sender);
+
+        Events {receiver}
+    }
+}
+
+// without the handlers the game still plays, it just can't tidy up on these signals
+#[cfg(unix)]
+fn listen_for_signals(sender: mpsc::Sender<Event>) {
+    use signal_hook::consts::{SIGCONT, SIGHUP, SIGTERM, SIGTSTP, SIGWINCH};
+    use signal_hook::iterator::Signals;
+
+    let mut signals = match Signals::new([SIGWINCH, SIGCONT, SIGTSTP, SIGTERM, SIGHUP]) {
+        Ok(signals) => signals,
+        Err(_) => return,
+    };
+    thread::spawn(move || {
+        for signal in signals.forever() {
+            let event = match signal {
+                SIGTSTP => Event::Suspend,
+                SIGTERM | SIGHUP => Event::Terminate,
+                _ => Event::Redraw,
+            };
+            if sender.send(event).is_err() {
+                return;
+            }
+        }
+    });
+}
+
+impl Default for Events {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// terminals don't report key releases, so a held key shows up as a stream of
+// auto-repeated presses. Presses of the same key closer than this are treated
+// as the key still being held down.
+const REPEAT_WINDOW: Duration = Duration::from_millis(150);
+
+// the actions whose keys are down, as far as the presses tell. An action is held once
+// its key repeats, and for as long as it keeps repeating.
+#[derive(Default)]
+pub struct HeldActions {
+    // when each action was first pressed and last seen, the latest last
+    presses: Vec<(Action, Instant, Instant)>,
+}
+
+impl HeldActions {
+    pub fn new() -> Self {
+        HeldActions::default()
+    }
+
+    pub fn press(&mut self, action: Action, now: Instant) {
+        let pressed = match self.presses.iter().position(|(held, ..)| *held == action) {
+            Some(idx) => match self.presses.remove(idx) {
+                (_, pressed, seen) if now.duration_since(seen) <= REPEAT_WINDOW => pressed,
+                _ => now,
+            },
+            None => now,
+        };
+        self.presses.push((action, pressed, now));
+    }
+
+    // the actions pressed since a moment or still held now, in the order they were seen
+    pub fn held(&self, since: Instant, now: Instant) -> impl Iterator<Item = Action> + '_ {
+        self.presses.iter().filter(move |(_, pressed, seen)| {
+            *seen >= since || (seen > pressed && now.duration_since(*seen) <= REPEAT_WINDOW)
+        }).map(|(action, ..)| *action)
+    }
+}
+
+pub enum Shift {
+    None,
+    // a fresh press of the key, moves one cell
+    Press,
+    // cells moved by a key that is being held down
+    Cells(usize),
+    ToWall,
+}
+
+struct HeldKey {
+    key: Key,
+    pressed: Instant,
+    last_seen: Instant,
+    last_shift: Instant,
+}
+
+// applies DAS/ARR on top of the terminal's own key repeat
+pub struct AutoShift {
+    das: Duration,
+    arr: Duration,
+    held: Option<HeldKey>,
+}
+
+impl AutoShift {
+    pub fn new(handling: &Handling) -> Self {
+        AutoShift {
+            das: Duration::from_millis(handling.das_ms as u64),
+            arr: Duration::from_millis(handling.arr_ms as u64),
+            held: None,
+        }
+    }
+
+    pub fn on_move(&mut self, key: Key, now: Instant) -> Shift {
+        let held = match self.held.as_mut() {
+            Some(held) if held.key == key
+                && now.duration_since(held.last_seen) <= REPEAT_WINDOW => held,
+            _ => {
+                self.held = Some(HeldKey {key, pressed: now, last_seen: now, last_shift: now});
+                return Shift::Press;
+            }
+        };
+        held.last_seen = now;
+
+        if now.duration_since(held.pressed) < self.das {
+            return Shift::None;
+        }
+        if self.arr.as_millis() == 0 {
+            return Shift::ToWall;
+        }
+
+        let cells = now.duration_since(held.last_shift).as_millis() / self.arr.as_millis();
+        held.last_shift = now;
+        Shift::Cells((cells as usize).max(1))
+    }
+
+    // any other key interrupts a held move
+    pub fn release(&mut self) {
+        self.held = None;
+    }
+}
+
+// textual representation of keys as used in the config file
+pub mod key_format {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use super::Key;
+
+    pub fn to_string(key: &Key) -> String {
+        match key {
+            Key::Char(' ') => "Space".to_string(),
+            Key::Char('\n') => "Enter".to_string(),
+            Key::Char('\t') => "Tab".to_string(),
+            Key::Char(c) => c.to_string(),
+            Key::Alt(c) => format!("Alt-{}", c),
+            Key::Ctrl(c) => format!("Ctrl-{}", c),
+            Key::F(n) => format!("F{}", n),
+            Key::Left => "Left".to_string(),
+            Key::Right => "Right".to_string(),
+            Key::Up => "Up".to_string(),
+            Key::Down => "Down".to_string(),
+            Key::Backspace => "Backspace".to_string(),
+            Key::Home => "Home".to_string(),
+            Key::End => "End".to_string(),
+            Key::PageUp => "PageUp".to_string(),
+            Key::PageDown => "PageDown".to_string(),
+            Key::BackTab => "BackTab".to_string(),
+            Key::Delete => "Delete".to_string(),
+            Key::Insert => "Insert".to_string(),
+            Key::Esc => "Esc".to_string(),
+        }
+    }
+
+    pub fn parse(name: &str) -> Result<Key, String> {
+        let mut chars = name.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return Ok(Key::Char(c));
+        }
+
+        let single = |rest: &str| {
+            let mut chars = rest.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Ok(c),
+                _ => Err(format!("unknown key `{}`", name)),
+            }
+        };
+
+        if let Some(rest) = name.strip_prefix("Ctrl-") {
+            return single(rest).map(Key::Ctrl);
+        }
+        if let Some(rest) = name.strip_prefix("Alt-") {
+            return single(rest).map(Key::Alt);
+        }
+        if let Some(n) = name.strip_prefix('F').and_then(|n| n.parse::<u8>().ok()) {
+            return Ok(Key::F(n));
+        }
+
+        Ok(match name {
+            "Space" => Key::Char(' '),
+            "Enter" => Key::Char('\n'),
+            "Tab" => Key::Char('\t'),
+            "Left" => Key::Left,
+            "Right" => Key::Right,
+            "Up" => Key::Up,
+            "Down" => Key::Down,
+            "Backspace" => Key::Backspace,
+            "Home" => Key::Home,
+            "End" => Key::End,
+            "PageUp" => Key::PageUp,
+            "PageDown" => Key::PageDown,
+            "BackTab" => Key::BackTab,
+            "Delete" => Key::Delete,
+            "Insert" => Key::Insert,
+            "Esc" => Key::Esc,
+            _ => return Err(format!("unknown key `{}`", name)),
+        })
+    }
+
+    pub fn serialize<S: Serializer>(key: &Key, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&to_string(key))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Key, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        parse(&name).map_err(serde::de::Error::custom)
+    }
+}
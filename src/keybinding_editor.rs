@@ -0,0 +1,105 @@
+use tui::{
+    backend::Backend,
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+use crate::controls::{Action, KeyMap};
+use crate::input::{key_format, Key};
+
+pub enum EditorOutcome {
+    Continue,
+    // the editor was closed, the bindings should be persisted
+    Close,
+}
+
+// interactive screen for rebinding the controls. Navigation keys are fixed so
+// a broken binding can't lock the player out of the editor.
+pub struct KeyBindingEditor {
+    selected: usize,
+    capturing: bool,
+    message: Option<String>,
+}
+
+impl KeyBindingEditor {
+    pub fn new() -> Self {
+        KeyBindingEditor { selected: 0, capturing: false, message: None }
+    }
+
+    fn selected_action(&self) -> Action {
+        Action::ALL[self.selected]
+    }
+
+    pub fn handle_key(&mut self, key: Key, keymap: &mut KeyMap) -> EditorOutcome {
+        if self.capturing {
+            self.capturing = false;
+            self.message = match key {
+                Key::Esc => None,
+                key => match keymap.bind(self.selected_action(), key) {
+                    Ok(()) => None,
+                    Err(conflict) => Some(format!("`{}` is already bound to {}",
+                                                  key_format::to_string(&conflict.key),
+                                                  conflict.action)),
+                },
+            };
+            return EditorOutcome::Continue;
+        }
+
+        self.message = None;
+        match key {
+            Key::Up | Key::Char('k') => {
+                self.selected = (self.selected + Action::ALL.len() - 1) % Action::ALL.len();
+            }
+            Key::Down | Key::Char('j') => {
+                self.selected = (self.selected + 1) % Action::ALL.len();
+            }
+            Key::Char('\n') => self.capturing = true,
+            Key::Backspace | Key::Delete if !keymap.unbind_last(self.selected_action()) => {
+                self.message = Some("every action needs at least one key".to_string());
+            }
+            Key::Esc | Key::Char('q') => return EditorOutcome::Close,
+            _ => (),
+        }
+        EditorOutcome::Continue
+    }
+
+    pub fn draw<B: Backend>(&self, keymap: &KeyMap, area: Rect, frame: &mut Frame<B>) {
+        let mut lines: Vec<Spans> = Action::ALL.iter().enumerate().map(|(idx, action)| {
+            let keys = keymap.keys_for(*action).iter()
+                .map(key_format::to_string)
+                .collect::<Vec<String>>()
+                .join(", ");
+            let style = if idx == self.selected {
+                Style::default().add_modifier(Modifier::REVERSED)
+            } else {
+                Style::default()
+            };
+            Spans::from(Span::styled(format!("{:<24}{}", action.to_string(), keys), style))
+        }).collect();
+
+        lines.push(Spans::from(""));
+        lines.push(Spans::from(if self.capturing {
+            format!("Press a key for {} (Esc cancels)", self.selected_action())
+        } else {
+            "Enter: add key  Backspace: remove key  q: save and close".to_string()
+        }));
+        if let Some(message) = &self.message {
+            lines.push(Spans::from(Span::styled(message.clone(),
+                                                Style::default().fg(Color::Red))));
+        }
+
+        let paragraph = Paragraph::new(lines)
+            .block(Block::default().title("Key bindings").borders(Borders::ALL))
+            .alignment(Alignment::Left);
+        frame.render_widget(paragraph, area);
+    }
+}
+
+impl Default for KeyBindingEditor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
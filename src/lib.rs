@@ -0,0 +1,24 @@
+pub mod matrix;
+pub mod piece;
+pub mod pile;
+pub mod tetris;
+pub mod config;
+pub mod controls;
+pub mod input;
+pub mod keybinding_editor;
+pub mod cli;
+pub mod replay;
+pub mod app;
+pub mod highscores;
+pub mod menu;
+pub mod ui;
+pub mod theme;
+pub mod animation;
+pub mod terminal;
+pub mod crash;
+pub mod bot;
+pub mod tbp;
+pub mod simulate;
+pub mod env;
+#[cfg(feature = "python")]
+pub mod python;
@@ -1,288 +1,133 @@
-// for input
 use std::{error::Error, io};
-use termion::{event::Key, raw::IntoRawMode, screen::AlternateScreen};
-use termion::input::TermRead;
 
-use tui::{
-    backend::TermionBackend,
-    layout::{Alignment, Constraint, Direction, Layout},
-    style::{Color, Modifier, Style},
-    widgets::{Block, BorderType, Borders, Paragraph},
-    text::Span,
-    Terminal,
-};
+use clap::Parser;
 
-use std::time::Duration;
-use std::sync::mpsc;
-use std::{thread, time};
+use std::path::Path;
 
-struct Events {
-    receiver: mpsc::Receiver<Key>
-}
-
-impl Events {
-    fn new() -> Self {
-        let (sender, receiver) = mpsc::channel();
-
-        // just spawn a thread that will listen for key presses
-        thread::spawn(move || {
-            for key_press in std::io::stdin().keys() {
-                if let Ok(key) = key_press {
-                    if let Err(err) = sender.send(key) {
-                        eprintln!("Whops {}", err);
-                    }
-                }
-            }
-        });
+use tui_tetris::{bot, config, crash, replay, simulate, terminal, ui};
+use tui_tetris::bot::{Bot, Heuristic, Player};
+use tui_tetris::config::{Config, Rules};
+use tui_tetris::input::Events;
+use tui_tetris::cli::{Cli, Command, Format, SimulateArgs};
+use tui_tetris::replay::Replay;
+use tui_tetris::app::{App, Screen, Session};
+use tui_tetris::tbp::TbpBot;
 
-        Events {receiver}
-    }
+fn load_replay(path: &Path) -> Result<Replay, String> {
+    Replay::load(path).map_err(|err| format!("failed to load {}: {}", path.display(), err))
 }
 
-pub mod matrix;
-pub mod piece;
-pub mod pile;
-pub mod tetris;
-pub mod config;
-
-use piece::Piece;
-use tetris::Tetris;
-
-fn get_tui_color(piece_color: piece::PieceColor) -> Color {
-    match piece_color {
-        piece::PieceColor::Red => Color::Red,
-        piece::PieceColor::Green => Color::Green,
-        piece::PieceColor::LightBlue => Color::LightBlue,
-        piece::PieceColor::Blue => Color::Blue,
-        piece::PieceColor::LightYellow => Color::LightYellow,
-        piece::PieceColor::Yellow => Color::Yellow,
-        piece::PieceColor::Magenta => Color::Magenta,
+fn simulate(rules: &Rules, args: &SimulateArgs) -> Result<(), Box<dyn Error>> {
+    if args.weights.as_ref().is_some_and(|weights| weights.len() != 5) {
+        return Err("--weights takes five comma separated numbers".into());
     }
-}
-
-fn render_playing_piece(piece: &Piece, block: &Block, color_hint: Option<Color>,
-                frame: &mut tui::terminal::Frame<TermionBackend<AlternateScreen
-                <termion::raw::RawTerminal<std::io::Stdout>>>>) {
-    piece.get_positions().iter().map(|(i, j)| {
-        tui::layout::Rect{
-            x: (*j * config::BLOCK_WIDTH) as u16,
-            y: (*i * config::BLOCK_HEIGHT) as u16,
-            width: config::BLOCK_WIDTH as u16, height: config::BLOCK_HEIGHT as u16}
-    }).for_each(|rect| {
-        frame.render_widget(block.clone()
-             .style(Style::default()
-                    .bg(color_hint.unwrap_or(
-                            get_tui_color(piece::get_piece_color(&piece.piece_type))))), rect);
-    });
-}
-
-fn render_utility_piece(piece: &Piece, block: &Block, color_hint: Option<Color>,
-                frame: &mut tui::terminal::Frame<TermionBackend<AlternateScreen
-                <termion::raw::RawTerminal<std::io::Stdout>>>>) {
-    piece.get_positions().iter().map(|(i, j)| {
-        tui::layout::Rect{
-            x: config::END_PLAYING_SCREEN_X as u16+ 2 + (*j * 6) as u16,
-            y: (*i * 3) as u16,
-            width: 6 as u16, height: 3 as u16}
-    }).for_each(|rect| {
-        frame.render_widget(block.clone()
-             .style(Style::default()
-                    .bg(color_hint.unwrap_or(
-                            get_tui_color(piece::get_piece_color(&piece.piece_type))))), rect);
+    let threads = args.threads.unwrap_or_else(|| {
+        std::thread::available_parallelism().map_or(1, |count| count.get())
     });
-}
-
-fn put_message_on_screen(
-                message: &str,
-                terminal:
-                &mut Terminal<TermionBackend<AlternateScreen<
-                    termion::raw::RawTerminal<std::io::Stdout>>>>) {
-
-    terminal.draw(|frame| {
-        let screen = Layout::default()
-            .direction(Direction::Vertical)
-            .margin(5)
-            .constraints([Constraint::Ratio(1, 3),
-                          Constraint::Ratio(1, 3),
-                          Constraint::Ratio(1, 3)].as_ref())
-            .split(frame.size());
-
-        let screen = Layout::default()
-            .direction(Direction::Horizontal)
-            .margin(5)
-            .constraints([Constraint::Ratio(1, 3),
-                          Constraint::Ratio(1, 3),
-                          Constraint::Ratio(1, 3)].as_ref())
-            .split(screen[1]);
-
-        let paragraph = Paragraph::new(message)
-            .block(Block::default().borders(Borders::ALL))
-            .alignment(Alignment::Center);
-        frame.render_widget(paragraph, screen[1]);
-    }).expect("Failed to display image on screen");
+    let make_player = || -> io::Result<Box<dyn Player>> {
+        match (&args.tbp, &args.weights) {
+            (Some(command), _) => Ok(Box::new(TbpBot::spawn(command)?)),
+            (None, Some(weights)) => Ok(Box::new(Bot::new(Box::new(Heuristic {
+                holes: weights[0],
+                bumpiness: weights[1],
+                aggregate_height: weights[2],
+                wells: weights[3],
+                lines: weights[4],
+            })))),
+            (None, None) => Ok(Box::new(Bot::default())),
+        }
+    };
+    let results = simulate::run(args.games, threads, args.seed, make_player, |player, seed| {
+        simulate::play(player, rules, args.mode, seed, args.max_pieces)
+    })?;
+
+    let output = match (args.format, args.per_game) {
+        (Format::Json, false) => serde_json::to_string_pretty(&simulate::summarize(&results))?,
+        (Format::Json, true) => serde_json::to_string_pretty(&results)?,
+        (Format::Csv, false) => simulate::summarize(&results).to_csv(),
+        (Format::Csv, true) => std::iter::once(
+            "seed,score,lines,pieces,time_ms,topped_out\n".to_string())
+            .chain(results.iter().map(|result| format!("{},{},{},{},{},{}\n",
+                result.seed, result.score, result.lines, result.pieces, result.time_ms,
+                result.topped_out)))
+            .collect(),
+    };
+    match &args.output {
+        Some(path) => std::fs::write(path, output)?,
+        None => print!("{}", output),
+    }
+    Ok(())
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::parse();
+
+    let config_path = cli.config.clone().or_else(config::default_path);
+    // the overrides are checked along with the config, but kept out of it so they are not
+    // saved with it
+    let loaded = Config::load(cli.config.as_deref()).and_then(|config| {
+        let mut checked = config.clone();
+        cli.overrides.apply(&mut checked.rules);
+        checked.validate().map(|_| config)
+    });
+    let config = match loaded {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(2);
+        }
+    };
 
-    let stdout = io::stdout().into_raw_mode()?;
-    let stdout = AlternateScreen::from(stdout);
-    let backend = TermionBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
-
-    let events = Events::new();
-    let mut game = Tetris::new();
-
-    loop {
-        // render tui
-        terminal.draw(|f| {
-            let screen = Layout::default()
-                .direction(Direction::Horizontal)
-                .constraints([Constraint::Length(
-                        (config::RIGHT_THRESHOLD * config::BLOCK_WIDTH) as u16),
-                              Constraint::Length(30),
-                              Constraint::Min(0)].as_ref())
-                .split(f.size());
-
-            let block = Block::default()
-                .title("Tetris")
-                .borders(Borders::ALL).border_type(BorderType::Rounded);
-            f.render_widget(block, screen[0]);
-
-            let other = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints([Constraint::Ratio(1, 3),
-                              Constraint::Ratio(1, 3),
-                              Constraint::Ratio(1, 3)].as_ref())
-                .split(screen[1]);
-
-            let block = Block::default()
-                .title("Next")
-                .borders(Borders::ALL);
-            f.render_widget(block, other[0]);
-
-            let block = Block::default()
-                .title("Spare")
-                .borders(Borders::ALL);
-            f.render_widget(block, other[1]);
-
-            let block = Block::default()
-                .title("Score")
-                .borders(Borders::ALL);
-            f.render_widget(block, other[2]);
-
-
-            let block = Block::default()
-                .borders(Borders::ALL)
-                .border_type(BorderType::Rounded)
-                .style(Style::default().bg(Color::DarkGray));
-
-            render_utility_piece(&game.spare_piece, &block.clone(), None, f);
-            render_utility_piece(&game.next_piece, &block.clone(), None, f);
-            render_playing_piece(&game.projected_piece,
-                         &block.clone(), Some(Color::Gray), f);
-            render_playing_piece(&game.current_piece, &block, None, f);
-
-            let potentionally_completed_lines = game.pile.get_complete_lines_with(
-                &game.projected_piece.get_positions());
-
-            game.pile.map.iter().map(|((i, j), piece_color)| {
-                let color : Color;
-                if potentionally_completed_lines.contains(i) {
-                    color = Color::Rgb(200, 200, 200);
-                } else {
-                    color = get_tui_color(piece_color.clone());// change this to Color::DarkGrey if you don't like colored pile
-                }
-                (tui::layout::Rect{
-                    x: *j as u16 * config::BLOCK_WIDTH as u16,
-                    y: *i as u16 * config::BLOCK_HEIGHT as u16,
-                    width: config::BLOCK_WIDTH as u16, height: config::BLOCK_HEIGHT as u16},
-                    color)
-            }).for_each(|(rect, color)| {
-                f.render_widget(block.clone().style(Style::default().bg(color)), rect);
-            });
-
-            let block = Block::default()
-                .title(Span::styled(format!("Score: {}", game.score),
-                Style::default().add_modifier(Modifier::BOLD)));
-            f.render_widget(block.clone(), tui::layout::Rect {
-                x: other[2].x + 2,
-                y: other[2].y + 2,
-                height: other[2].height - 2,
-                width: other[2].width - 2,
-            });
-
-            let block = Block::default()
-                .title(Span::styled(format!("Last combo: {}",  game.last_combo),
-                Style::default().add_modifier(Modifier::BOLD)));
-            f.render_widget(block.clone(), tui::layout::Rect {
-                x: other[2].x + 2,
-                y: other[2].y + 3,
-                height: other[2].height - 3,
-                width: other[2].width - 2,
-            });
+    if let Some(Command::Simulate(args)) = &cli.command {
+        let mut rules = config.rules.clone();
+        cli.overrides.apply(&mut rules);
+        return simulate(&rules, args);
+    }
 
-            let block = Block::default()
-                .title(Span::styled(format!("Tick speed: {}",  game.get_tick_speed()),
-                Style::default().add_modifier(Modifier::BOLD)));
-            f.render_widget(block.clone(), tui::layout::Rect {
-                x: other[2].x + 2,
-                y: other[2].y + 4,
-                height: other[2].height - 4,
-                width: other[2].width - 2,
-            });
-        })?;
+    if let Some(path) = &cli.verify_replay {
+        println!("{}", ui::summary(&load_replay(path)?.play_all()));
+        return Ok(());
+    }
+    let watched = cli.replay.as_deref().map(load_replay).transpose()?;
 
-        if game.is_over() {
-            break;
-        }
-        match events.receiver.recv_timeout(Duration::from_millis(
-                game.get_timeout() as u64)) {
-            Ok(key) => {
-                match key {
-                    Key::Char('q') => break,
-                    Key::Left => game.move_left(),
-                    Key::Right => game.move_right(),
-                    Key::Down => game.move_down(),
-                    Key::Char('a') => game.safe_rotate_counter_clockwise(),
-                    Key::Char('d') => game.safe_rotate_clockwise(),
-                    Key::Char('s') => game.use_spare(),
-                    Key::Char('p') => {
-                        loop {
-                            put_message_on_screen("Game is paused", &mut terminal);
-                            match events.receiver.recv() {
-                                Ok(key) => {
-                                    match key {
-                                        Key::Char('p') => break,
-                                        // only 'p' can restart the game
-                                        _ => ()
-                                    }
-                                }
-                                Err(e) => {
-                                    eprintln!("Failed to read key during pause: {}", e);
-                                }
-                            }
-                        }
-                    }
-                    Key::Char(' ') => game.drop_to_bottom(),
-                    _ => continue
-                }
-            },
-            Err(mpsc::RecvTimeoutError::Timeout) => {
-                if game.can_move_down() {
-                    game.move_down();
-                } else if game.should_finish_turn() {
-                    if game.finish_turn() {
-                        break;
-                    }
-                } else {
-                    game.advance_stuck();
-                }
-            },
-            _ => eprintln!("WTF")
+    let resumed = if cli.resume {
+        let path = replay::autosave_path().ok_or("no place to look for a saved game")?;
+        if !path.exists() {
+            return Err("there is no saved game to resume".into());
         }
+        Some(load_replay(&path)?)
+    } else {
+        None
+    };
+
+    let external = cli.tbp.as_deref().map(|command| TbpBot::spawn(command)
+        .map_err(|err| format!("failed to start the bot \"{}\": {}", command, err)))
+        .transpose()?;
+
+    // dropped after the app and with it the terminal, to report a crash on a sane screen
+    let _crash_guard = crash::install();
+    let terminal = terminal::open()?;
+
+    let mut app = App::new(config, config_path, terminal, Events::new());
+    app.seed = cli.seed;
+    app.record_path = cli.record.clone();
+    app.overrides = cli.overrides.clone();
+    if cli.bot {
+        app.bot = Some(Box::new(bot::Bot::default()));
+    }
+    if let Some(external) = external {
+        app.bot = Some(Box::new(external));
     }
 
-    put_message_on_screen(&format!("Your score is {}", game.score), &mut terminal);
-    std::thread::sleep(time::Duration::from_secs(2));
-    Ok(())
+    if let Some(replay) = watched {
+        return Ok(app.watch_replay(&replay)?);
+    }
+
+    // jump straight into a game when the command line asks for one
+    let screen = match (resumed, cli.mode) {
+        (Some(recording), _) => Screen::Playing(Box::new(Session::resume(recording))),
+        (None, Some(mode)) => Screen::Playing(Box::new(app.new_session(mode))),
+        (None, None) => Screen::Title,
+    };
+    Ok(app.run(screen)?)
 }
@@ -0,0 +1,46 @@
+
+use crate::input::Key;
+
+pub enum MenuEvent {
+    None,
+    Chosen(usize),
+    // left/right on an item, for cycling through the values of a setting
+    Previous(usize),
+    Next(usize),
+    Back,
+}
+
+// keeps track of the selected item of a vertical list of options
+pub struct Menu {
+    pub selected: usize,
+}
+
+impl Menu {
+    pub fn new() -> Self {
+        Menu { selected: 0 }
+    }
+
+    pub fn handle_key(&mut self, key: Key, item_count: usize) -> MenuEvent {
+        match key {
+            Key::Up | Key::Char('k') => {
+                self.selected = (self.selected + item_count - 1) % item_count;
+                MenuEvent::None
+            }
+            Key::Down | Key::Char('j') => {
+                self.selected = (self.selected + 1) % item_count;
+                MenuEvent::None
+            }
+            Key::Left | Key::Char('h') => MenuEvent::Previous(self.selected),
+            Key::Right | Key::Char('l') => MenuEvent::Next(self.selected),
+            Key::Char('\n') => MenuEvent::Chosen(self.selected),
+            Key::Esc | Key::Char('q') => MenuEvent::Back,
+            _ => MenuEvent::None,
+        }
+    }
+}
+
+impl Default for Menu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,7 +1,8 @@
 use crate::matrix::Matrix;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum PieceType {
     Square,
     L,
@@ -12,7 +13,7 @@ pub enum PieceType {
     ReverseWorm
 }
 
-#[derive(Clone)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum PieceColor {
     Red,
     Blue,
@@ -23,6 +24,42 @@ pub enum PieceColor {
     Magenta,
 }
 
+impl PieceType {
+    pub const ALL: [PieceType; 7] = [
+        PieceType::Square, PieceType::L, PieceType::Straight, PieceType::ReverseL,
+        PieceType::T, PieceType::Worm, PieceType::ReverseWorm,
+    ];
+
+    pub fn index(&self) -> usize {
+        match self {
+            PieceType::Square => 0,
+            PieceType::L => 1,
+            PieceType::Straight => 2,
+            PieceType::ReverseL => 3,
+            PieceType::T => 4,
+            PieceType::Worm => 5,
+            PieceType::ReverseWorm => 6,
+        }
+    }
+
+    // the usual single letter name of the piece
+    pub fn letter(&self) -> char {
+        match self {
+            PieceType::Square => 'O',
+            PieceType::L => 'L',
+            PieceType::Straight => 'I',
+            PieceType::ReverseL => 'J',
+            PieceType::T => 'T',
+            PieceType::Worm => 'Z',
+            PieceType::ReverseWorm => 'S',
+        }
+    }
+
+    pub fn from_letter(letter: char) -> Option<PieceType> {
+        PieceType::ALL.iter().copied().find(|piece_type| piece_type.letter() == letter)
+    }
+}
+
 pub fn get_piece_color(piece_type: &PieceType) -> PieceColor {
     match piece_type {
         PieceType::Square => PieceColor::Red,
@@ -37,15 +74,7 @@ pub fn get_piece_color(piece_type: &PieceType) -> PieceColor {
 
 impl rand::distributions::Distribution<PieceType> for rand::distributions::Standard {
     fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> PieceType {
-        match rng.gen_range(0, 7) {
-            0 => PieceType::Square,
-            1 => PieceType::L,
-            2 => PieceType::Straight,
-            3 => PieceType::ReverseL,
-            4 => PieceType::T,
-            5 => PieceType::Worm,
-            _ => PieceType::ReverseWorm,
-        }
+        PieceType::ALL[rng.gen_range(0, PieceType::ALL.len())]
     }
 }
 
@@ -57,6 +86,8 @@ pub struct Piece {
     pub anchor_x: i16,
     pub anchor_y: i16,
     pub piece_type: PieceType,
+    // number of clockwise quarter turns from the spawn orientation
+    pub rotation: u8,
     template: Matrix,
 }
 
@@ -72,6 +103,7 @@ impl Piece {
         }
 
         self.template = new_template;
+        self.rotation = (self.rotation + 1) % 4;
     }
 
     pub fn rotate_counter_clockwise(&mut self) {
@@ -85,6 +117,7 @@ impl Piece {
         }
 
         self.template = new_template;
+        self.rotation = (self.rotation + 3) % 4;
     }
 
     fn get_piece_template(piece_type: &PieceType) -> Matrix {
@@ -99,22 +132,21 @@ impl Piece {
         }
     }
 
-    fn get_random_piece_type() -> PieceType {
-        rand::thread_rng().gen()
-    }
-
-    pub fn new_random_piece_at(anchor_x: i16, anchor_y: i16) -> Self {
-        let piece_type: PieceType = Self::get_random_piece_type();
-
-        Piece {anchor_x, anchor_y,
+    pub fn new(piece_type: PieceType, anchor_x: i16, anchor_y: i16) -> Self {
+        Piece {anchor_x, anchor_y, rotation: 0,
                template: Self::get_piece_template(&piece_type),
                piece_type}
     }
 
-    pub fn randomize(&mut self) {
-        let piece_type: PieceType = Self::get_random_piece_type();
+    pub fn new_random_piece_at<R: Rng>(anchor_x: i16, anchor_y: i16, rng: &mut R) -> Self {
+        Self::new(rng.gen(), anchor_x, anchor_y)
+    }
+
+    pub fn randomize<R: Rng>(&mut self, rng: &mut R) {
+        let piece_type: PieceType = rng.gen();
         self.template = Self::get_piece_template(&piece_type);
         self.piece_type = piece_type;
+        self.rotation = 0;
     }
 
     pub fn swap_figures(&mut self, other: &mut Piece) {
@@ -123,11 +155,15 @@ impl Piece {
 
         std::mem::swap(&mut self.piece_type,
                        &mut other.piece_type);
+
+        std::mem::swap(&mut self.rotation,
+                       &mut other.rotation);
     }
 
     // returns the piece to its initial orientation
     pub fn refresh(&mut self) {
         self.template = Self::get_piece_template(&self.piece_type);
+        self.rotation = 0;
     }
 
     pub fn get_positions(&self) -> [(usize, usize); 4] {
@@ -145,7 +181,7 @@ impl Piece {
             }
         }
 
-        return result;
+        result
     }
     // positions from this may include off-field coordinates
     pub fn get_positions_unsafe(&self) -> [(i16, i16); 4] {
@@ -161,7 +197,7 @@ impl Piece {
             }
         }
 
-        return result;
+        result
     }
 
     // this is for when a check has been done prior to this call
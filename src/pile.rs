@@ -1,95 +1,104 @@
-use crate::piece;
-use crate::matrix::Matrix;
-use std::collections::{HashMap, HashSet};
+use crate::piece::{self, PieceColor};
 
+// a board is at most this wide, one bit per column
+pub const MAX_WIDTH: usize = 64;
+
+#[derive(Clone)]
 pub struct Pile {
-    // for easier collision detection
-    pub field: Matrix,
-    // for easier block rendering
-    pub map: HashMap<(usize, usize), piece::PieceColor>
+    pub col_count: usize,
+    pub row_count: usize,
+    // one mask per row from the top, bit j is set when column j is taken
+    pub rows: Vec<u64>,
+    // row by row, the color of every taken cell, for rendering
+    pub colors: Vec<Option<PieceColor>>,
 }
 
 impl Pile {
-    pub fn contains(&self, coords: (usize, usize)) -> bool {
-        self.field[coords]
+    pub fn contains(&self, (i, j): (usize, usize)) -> bool {
+        self.rows[i] >> j & 1 == 1
     }
 
-    pub fn add(&mut self, piece: &piece::Piece) {
-        for coords in piece.get_positions().iter().cloned() {
-            self.field[coords] = true;
-            self.map.insert(coords, piece::get_piece_color(&piece.piece_type).clone());
-        }
+    // the mask of a row with every column taken
+    pub fn full_row(&self) -> u64 {
+        u64::MAX >> (MAX_WIDTH - self.col_count)
     }
 
-    fn is_complete_line_with(&self, line: usize, additional: &HashSet<(usize, usize)>) -> bool {
-        for (idx, value) in self.field.get_row(line).iter().enumerate() {
-            if !*value && !additional.contains(&(line, idx)){
-                return false;
-            }
+    pub fn add(&mut self, piece: &piece::Piece) {
+        let color = piece::get_piece_color(&piece.piece_type);
+        for (i, j) in piece.get_positions().iter().cloned() {
+            self.rows[i] |= 1 << j;
+            self.colors[i * self.col_count + j] = Some(color);
         }
-        return true;
     }
 
     pub fn get_complete_lines_with(&self, positions: &[(usize, usize); 4]) -> Vec<usize> {
-        let positions_set :HashSet<(usize, usize)> = positions.iter().cloned().collect();
-        let mut result : Vec<usize> = Vec::new();
+        let mut added = [0u64; 4];
+        for (idx, (i, _)) in positions.iter().enumerate() {
+            added[idx] = positions.iter().filter(|(row, _)| row == i)
+                .fold(0, |mask, (_, j)| mask | 1 << j);
+        }
 
-        for (i, _) in positions {
-            if self.is_complete_line_with(*i, &positions_set) {
+        let mut result : Vec<usize> = Vec::new();
+        for (idx, (i, _)) in positions.iter().enumerate() {
+            if self.rows[*i] | added[idx] == self.full_row() {
                 result.push(*i);
             }
         }
 
-        return result;
+        result
     }
 
-    fn copy_line(&mut self, from: usize, to: usize) {
-        self.field.copy_row(from, to);
-
-        for (idx, value) in self.field.get_row(to).iter().enumerate() {
-            if *value {
-                self.map.insert((to, idx),
-                    // just a random color for the unwrap fail as it can never happen
-                    self.map.get(&(from, idx)).cloned().unwrap_or(piece::PieceColor::Green));
-            }
-        }
+    // the rows cleanup_full_lines takes away
+    pub fn full_lines(&self) -> Vec<usize> {
+        let full = self.full_row();
+        (1..self.row_count).filter(|&i| self.rows[i] == full).collect()
     }
 
+    // the top row is never checked and goes away when anything is cleared
     pub fn cleanup_full_lines(&mut self) -> usize {
-        let mut current : usize = self.field.row_count- 1;
-        let mut cleaned_up = 0;
+        let full = self.full_row();
+        let mut current = self.row_count - 1;
 
-        for line in (1..self.field.row_count).rev() {
-            if self.is_complete_line_with(line, &HashSet::new()) {
-                self.remove_line(line);
-                cleaned_up += 1;
+        for line in (1..self.row_count).rev() {
+            if self.rows[line] == full {
                 continue;
-            } else {
-                if line != current {
-                    self.copy_line(line, current);
-                    self.remove_line(line);
-                }
-                current -= 1;
             }
+            if line != current {
+                self.rows[current] = self.rows[line];
+                let (above, below) = self.colors.split_at_mut(current * self.col_count);
+                below[..self.col_count].copy_from_slice(
+                    &above[line * self.col_count..(line + 1) * self.col_count]);
+            }
+            current -= 1;
         }
 
-        for line in 0..(current) {
-            self.remove_line(line);
+        let cleaned_up = current;
+        if cleaned_up > 0 {
+            self.rows[..=current].fill(0);
+            self.colors[..(current + 1) * self.col_count].fill(None);
         }
-        return cleaned_up;
+        cleaned_up
     }
 
-    fn remove_line(&mut self, line: usize) {
-        for (idx, value) in self.field.get_row_mut(line).iter_mut().enumerate() {
-            *value = false;
-            self.map.remove(&(line, idx));
-        }
+    // every taken cell with its color
+    pub fn cells(&self) -> impl Iterator<Item = ((usize, usize), PieceColor)> + '_ {
+        self.colors.iter().enumerate().filter_map(move |(idx, color)| {
+            color.map(|color| ((idx / self.col_count, idx % self.col_count), color))
+        })
+    }
+
+    // row by row from the top, true for taken cells
+    pub fn to_vec(&self) -> Vec<bool> {
+        self.colors.iter().map(Option::is_some).collect()
     }
 
     pub fn new(col_count: usize, row_count: usize) -> Self {
+        assert!(col_count <= MAX_WIDTH, "a pile is at most {} columns wide", MAX_WIDTH);
         Pile {
-            field: Matrix::new(col_count, row_count),
-            map: HashMap::new()
+            col_count,
+            row_count,
+            rows: vec![0; row_count],
+            colors: vec![None; col_count * row_count],
         }
     }
 }
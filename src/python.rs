@@ -0,0 +1,166 @@
+// the python extension module, built with `maturin develop --features python`
+
+// pyo3 0.22 generates a conversion of PyResult errors into themselves
+#![allow(clippy::useless_conversion)]
+
+use clap::ValueEnum;
+use pyo3::exceptions::{PyIndexError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde::de::value::{Error as ValueError, StrDeserializer};
+use serde::Deserialize;
+
+use crate::config::Config;
+use crate::env::{Action, Env, Observation, Rewards};
+use crate::piece::PieceType;
+use crate::tetris::{Input, Mode, RotationSystem};
+
+fn input_name(input: Input) -> String {
+    serde_json::to_value(input).ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn parse_input(name: &str) -> PyResult<Input> {
+    Input::deserialize(StrDeserializer::<ValueError>::new(name))
+        .map_err(|_| PyValueError::new_err(format!("unknown input {:?}", name)))
+}
+
+fn parse_enum<T: ValueEnum>(what: &str, name: &str) -> PyResult<T> {
+    T::from_str(name, true).map_err(|_| PyValueError::new_err(format!("unknown {} {:?}", what, name)))
+}
+
+fn letter(piece_type: PieceType) -> String {
+    piece_type.letter().to_string()
+}
+
+fn observation<'py>(py: Python<'py>, observation: &Observation) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new_bound(py);
+    let rows: Vec<Vec<bool>> = observation.board.chunks(observation.width)
+        .map(|row| row.to_vec()).collect();
+    dict.set_item("board", rows)?;
+    dict.set_item("current", letter(observation.current))?;
+    dict.set_item("current_cells", observation.current_cells.to_vec())?;
+    dict.set_item("queue", observation.queue.iter().copied().map(letter).collect::<Vec<_>>())?;
+    dict.set_item("hold", letter(observation.hold))?;
+    dict.set_item("can_hold", observation.can_hold)?;
+    Ok(dict)
+}
+
+// a game driven from python, everything follows from the seed
+#[pyclass(name = "Game")]
+struct PyGame {
+    env: Env,
+}
+
+#[pymethods]
+impl PyGame {
+    #[new]
+    #[pyo3(signature = (seed=0, mode="marathon", width=None, height=None, rotation=None,
+                        preview=None, rewards=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(seed: u64, mode: &str, width: Option<usize>, height: Option<usize>,
+           rotation: Option<&str>, preview: Option<usize>,
+           rewards: Option<&Bound<'_, PyDict>>) -> PyResult<Self> {
+        let mut config = Config::default();
+        let rules = &mut config.rules;
+        rules.width = width.unwrap_or(rules.width);
+        rules.height = height.unwrap_or(rules.height);
+        rules.preview_count = preview.unwrap_or(rules.preview_count);
+        if let Some(rotation) = rotation {
+            rules.rotation = parse_enum::<RotationSystem>("rotation system", rotation)?;
+        }
+        config.validate().map_err(|err| PyValueError::new_err(err.to_string()))?;
+
+        let mut weights = Rewards::default();
+        for (key, value) in rewards.into_iter().flat_map(|rewards| rewards.iter()) {
+            let value: f64 = value.extract()?;
+            match key.extract::<String>()?.as_str() {
+                "lines" => weights.lines = value,
+                "score" => weights.score = value,
+                "survival" => weights.survival = value,
+                "top_out" => weights.top_out = value,
+                other => return Err(PyValueError::new_err(format!("unknown reward {:?}", other))),
+            }
+        }
+
+        let mut env = Env::new(config.rules, parse_enum::<Mode>("mode", mode)?, weights);
+        env.reset(seed);
+        Ok(PyGame { env })
+    }
+
+    // starts over, returns the first observation
+    fn reset<'py>(&mut self, py: Python<'py>, seed: u64) -> PyResult<Bound<'py, PyDict>> {
+        observation(py, &self.env.reset(seed))
+    }
+
+    // takes an input name like "move_left" or the index of a placement,
+    // returns (observation, reward, done)
+    fn step<'py>(&mut self, py: Python<'py>, action: &Bound<'py, PyAny>)
+        -> PyResult<(Bound<'py, PyDict>, f64, bool)> {
+        let action = match action.extract::<usize>() {
+            Ok(idx) => Action::Place(idx),
+            Err(_) => Action::Input(parse_input(&action.extract::<String>()?)?),
+        };
+        let step = self.env.step(action).map_err(|err| PyIndexError::new_err(err.to_string()))?;
+        Ok((observation(py, &step.observation)?, step.reward, step.done))
+    }
+
+    // every spot the current or held piece can lock in, the index is what step takes
+    fn placements<'py>(&mut self, py: Python<'py>) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        self.env.placements().iter().map(|plan| {
+            let dict = PyDict::new_bound(py);
+            dict.set_item("piece", letter(plan.piece.piece_type))?;
+            dict.set_item("cells", plan.piece.get_positions().to_vec())?;
+            dict.set_item("inputs", plan.inputs.iter().copied().map(input_name)
+                .collect::<Vec<_>>())?;
+            dict.set_item("hold", plan.inputs.first() == Some(&Input::Hold))?;
+            Ok(dict)
+        }).collect()
+    }
+
+    fn observation<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        observation(py, &self.env.observation())
+    }
+
+    // rows from the top, true for filled cells
+    fn board(&self) -> Vec<Vec<bool>> {
+        let pile = &self.env.game().pile;
+        pile.to_vec().chunks(pile.col_count).map(|row| row.to_vec()).collect()
+    }
+
+    #[getter]
+    fn score(&self) -> usize {
+        self.env.game().score
+    }
+
+    #[getter]
+    fn lines(&self) -> usize {
+        self.env.game().lines
+    }
+
+    #[getter]
+    fn pieces(&self) -> usize {
+        self.env.game().stats.pieces
+    }
+
+    #[getter]
+    fn seed(&self) -> u64 {
+        self.env.game().seed
+    }
+
+    #[getter]
+    fn is_over(&self) -> bool {
+        self.env.game().is_over()
+    }
+}
+
+#[pymodule]
+fn tui_tetris(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyGame>()?;
+    let inputs = [Input::MoveLeft, Input::MoveRight, Input::SoftDrop, Input::HardDrop,
+                  Input::RotateCw, Input::RotateCcw, Input::Rotate180, Input::Hold,
+                  Input::Gravity];
+    module.add("INPUTS", inputs.iter().copied().map(input_name).collect::<Vec<_>>())?;
+    Ok(())
+}
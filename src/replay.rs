@@ -0,0 +1,87 @@
+use serde::{Deserialize, Serialize};
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::config::{self, Rules};
+use crate::tetris::{Input, Mode, Tetris};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ReplayEvent {
+    // game time at which the input was applied
+    pub at_ms: u64,
+    pub input: Input,
+    // the input came from a held key rather than a fresh press
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub repeat: bool,
+}
+
+// everything needed to play a game again: its settings and every input in order
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    pub mode: Mode,
+    pub rules: Rules,
+    pub events: Vec<ReplayEvent>,
+    // game time of the last recorded moment, so time based modes end where they did
+    pub length_ms: u64,
+}
+
+impl Replay {
+    pub fn new(seed: u64, mode: Mode, rules: &Rules) -> Self {
+        Replay { seed, mode, rules: rules.clone(), events: Vec::new(), length_ms: 0 }
+    }
+
+    pub fn record(&mut self, at_ms: u64, input: Input, repeat: bool) {
+        self.events.push(ReplayEvent { at_ms, input, repeat });
+        self.length_ms = at_ms;
+    }
+
+    pub fn finish(&mut self, at_ms: u64) {
+        self.length_ms = self.length_ms.max(at_ms);
+    }
+
+    // a fresh game whose clock is driven by the replay
+    pub fn start(&self) -> Tetris {
+        let mut game = Tetris::new(&self.rules, self.mode, self.seed);
+        game.set_elapsed_ms(0);
+        game
+    }
+
+    pub fn apply(game: &mut Tetris, event: &ReplayEvent) {
+        game.set_elapsed_ms(event.at_ms);
+        if event.repeat {
+            game.apply_repeat(event.input);
+        } else {
+            game.apply(event.input);
+        }
+    }
+
+    // replays the whole game without rendering anything
+    pub fn play_all(&self) -> Tetris {
+        let mut game = self.start();
+        for event in self.events.iter() {
+            Self::apply(&mut game, event);
+        }
+        game.set_elapsed_ms(self.length_ms);
+        game
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        Ok(serde_json::from_reader(io::BufReader::new(file))?)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let file = std::fs::File::create(path)?;
+        Ok(serde_json::to_writer(io::BufWriter::new(file), self)?)
+    }
+}
+
+// where an unfinished game is kept for `--resume`
+pub fn autosave_path() -> Option<PathBuf> {
+    config::data_dir().map(|dir| dir.join("save.json"))
+}
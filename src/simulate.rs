@@ -0,0 +1,147 @@
+use serde::Serialize;
+
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use crate::bot::Player;
+use crate::config::Rules;
+use crate::tetris::{Input, Mode, Outcome, Tetris};
+
+// simulated games have no gravity, the clock moves this much with every input
+pub const INPUT_MS: u64 = 50;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct GameResult {
+    pub seed: u64,
+    pub score: usize,
+    pub lines: usize,
+    pub pieces: usize,
+    pub time_ms: u64,
+    pub topped_out: bool,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Summary {
+    pub games: usize,
+    pub mean_lines: f64,
+    pub mean_score: f64,
+    pub mean_pieces: f64,
+    pub top_out_rate: f64,
+    pub score_min: usize,
+    pub score_p25: usize,
+    pub score_median: usize,
+    pub score_p75: usize,
+    pub score_p90: usize,
+    pub score_max: usize,
+}
+
+// plays one game until it is over or the player has had max_pieces turns
+pub fn play(player: &mut dyn Player, rules: &Rules, mode: Mode, seed: u64,
+            max_pieces: usize) -> io::Result<GameResult> {
+    let mut game = Tetris::new(rules, mode, seed);
+    game.set_elapsed_ms(0);
+    game.judge_finesse = false;
+
+    for _ in 0..max_pieces {
+        if game.is_over() {
+            break;
+        }
+        let inputs = player.plan(&game)?.map_or(vec![Input::HardDrop], |plan| plan.inputs);
+        for input in inputs {
+            game.set_elapsed_ms(game.elapsed_ms() + INPUT_MS);
+            game.apply(input);
+            if game.is_over() {
+                break;
+            }
+        }
+        game.skip_delays();
+    }
+
+    Ok(GameResult {
+        seed,
+        score: game.score,
+        lines: game.lines,
+        pieces: game.stats.pieces,
+        time_ms: game.elapsed_ms(),
+        topped_out: game.outcome() == Some(Outcome::ToppedOut),
+    })
+}
+
+// plays the games with seeds first_seed, first_seed + 1, ... spread over the
+// given number of threads, each with its own player. The results come back in
+// seed order whatever the number of threads.
+pub fn run<F>(games: usize, threads: usize, first_seed: u64, make_player: F,
+              play_one: impl Fn(&mut dyn Player, u64) -> io::Result<GameResult> + Sync)
+    -> io::Result<Vec<GameResult>>
+    where F: Fn() -> io::Result<Box<dyn Player>> + Sync {
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::with_capacity(games));
+
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads.clamp(1, games.max(1))).map(|_| {
+            scope.spawn(|| -> io::Result<()> {
+                let mut player = make_player()?;
+                loop {
+                    let idx = next.fetch_add(1, Ordering::Relaxed);
+                    if idx >= games {
+                        return Ok(());
+                    }
+                    let result = play_one(player.as_mut(), first_seed.wrapping_add(idx as u64))?;
+                    results.lock().expect("no worker panics while holding the lock")
+                        .push((idx, result));
+                }
+            })
+        }).collect();
+
+        workers.into_iter().try_for_each(|worker| {
+            worker.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+        })
+    })?;
+
+    let mut results = results.into_inner().expect("all the workers are done");
+    results.sort_by_key(|(idx, _)| *idx);
+    Ok(results.into_iter().map(|(_, result)| result).collect())
+}
+
+fn percentile(sorted: &[usize], percent: usize) -> usize {
+    if sorted.is_empty() {
+        return 0;
+    }
+    sorted[(sorted.len() - 1) * percent / 100]
+}
+
+pub fn summarize(results: &[GameResult]) -> Summary {
+    let count = results.len().max(1) as f64;
+    // an empty sum of floats is -0, which would come out as "-0.000"
+    let mean = |value: fn(&GameResult) -> f64| {
+        results.iter().map(value).fold(0.0, |sum, value| sum + value) / count
+    };
+    let mut scores: Vec<usize> = results.iter().map(|result| result.score).collect();
+    scores.sort_unstable();
+
+    Summary {
+        games: results.len(),
+        mean_lines: mean(|result| result.lines as f64),
+        mean_score: mean(|result| result.score as f64),
+        mean_pieces: mean(|result| result.pieces as f64),
+        top_out_rate: mean(|result| if result.topped_out { 1.0 } else { 0.0 }),
+        score_min: percentile(&scores, 0),
+        score_p25: percentile(&scores, 25),
+        score_median: percentile(&scores, 50),
+        score_p75: percentile(&scores, 75),
+        score_p90: percentile(&scores, 90),
+        score_max: percentile(&scores, 100),
+    }
+}
+
+impl Summary {
+    pub fn to_csv(&self) -> String {
+        format!("games,mean_lines,mean_score,mean_pieces,top_out_rate,\
+                 score_min,score_p25,score_median,score_p75,score_p90,score_max\n\
+                 {},{:.3},{:.3},{:.3},{:.4},{},{},{},{},{},{}\n",
+                self.games, self.mean_lines, self.mean_score, self.mean_pieces,
+                self.top_out_rate, self.score_min, self.score_p25, self.score_median,
+                self.score_p75, self.score_p90, self.score_max)
+    }
+}
@@ -0,0 +1,313 @@
+use serde::{Deserialize, Serialize};
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::bot::{self, Plan, Player};
+use crate::piece::PieceType;
+use crate::tetris::{finesse, Tetris};
+
+// the Tetris Bot Protocol: newline separated JSON messages over an external
+// bot's stdin and stdout, https://github.com/tetris-bot-protocol/tbp-spec
+
+// bots expect a board of 40 rows, taller boards send all of their rows
+pub const BOARD_ROWS: usize = 40;
+// a bot that takes longer than this to answer is given up on
+pub const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+// time a bot has to exit once it is told to quit, before it is killed
+const QUIT_TIMEOUT: Duration = Duration::from_millis(500);
+
+// pieces are single letters and empty cells are null, filled cells are "G"
+// since the pile doesn't remember which piece they came from
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FrontendMessage {
+    Rules {
+        randomizer: String,
+    },
+    Start {
+        hold: Option<char>,
+        // the current piece comes first
+        queue: Vec<char>,
+        combo: usize,
+        back_to_back: bool,
+        // rows from the bottom up
+        board: Vec<Vec<Option<char>>>,
+    },
+    Suggest,
+    Play {
+        #[serde(rename = "move")]
+        play: Move,
+    },
+    NewPiece {
+        piece: char,
+    },
+    Stop,
+    Quit,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BotMessage {
+    Info {
+        name: String,
+        version: String,
+        author: String,
+        #[serde(default)]
+        features: Vec<String>,
+    },
+    Ready,
+    Error {
+        reason: String,
+    },
+    Suggestion {
+        // best first
+        moves: Vec<Move>,
+    },
+    // messages from newer versions of the protocol are ignored
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Move {
+    pub location: Location,
+    #[serde(default)]
+    pub spin: Spin,
+}
+
+// where a piece is, by the SRS center of the piece with y going up from the floor
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Location {
+    #[serde(rename = "type")]
+    pub piece: char,
+    pub orientation: Orientation,
+    pub x: i32,
+    pub y: i32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Orientation {
+    North,
+    East,
+    South,
+    West,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Spin {
+    #[default]
+    None,
+    Mini,
+    Full,
+}
+
+impl Location {
+    // the (x, y) cells of the piece, None for an unknown piece
+    pub fn cells(&self) -> Option<[(i32, i32); 4]> {
+        let north = match PieceType::from_letter(self.piece)? {
+            PieceType::Straight => [(-1, 0), (0, 0), (1, 0), (2, 0)],
+            PieceType::Square => [(0, 0), (1, 0), (0, 1), (1, 1)],
+            PieceType::T => [(-1, 0), (0, 0), (1, 0), (0, 1)],
+            PieceType::L => [(-1, 0), (0, 0), (1, 0), (1, 1)],
+            PieceType::ReverseL => [(-1, 0), (0, 0), (1, 0), (-1, 1)],
+            PieceType::ReverseWorm => [(-1, 0), (0, 0), (0, 1), (1, 1)],
+            PieceType::Worm => [(-1, 1), (0, 1), (0, 0), (1, 0)],
+        };
+        Some(north.map(|(x, y)| {
+            let (x, y) = match self.orientation {
+                Orientation::North => (x, y),
+                Orientation::East => (y, -x),
+                Orientation::South => (-x, -y),
+                Orientation::West => (-y, x),
+            };
+            (self.x + x, self.y + y)
+        }))
+    }
+
+    // the same cells as a placement on a board of the given height
+    fn placement(&self, width: usize, height: usize) -> Option<finesse::Placement> {
+        let cells = self.cells()?;
+        let mut placement = [(0, 0); 4];
+        for (cell, (x, y)) in placement.iter_mut().zip(cells.iter()) {
+            if *x < 0 || *y < 0 || *x as usize >= width || *y as usize >= height {
+                return None;
+            }
+            *cell = (height - 1 - *y as usize, *x as usize);
+        }
+        placement.sort_unstable();
+        Some(placement)
+    }
+}
+
+impl FrontendMessage {
+    // the whole game, for a bot that starts on it or has to catch up with it
+    pub fn start(game: &Tetris) -> Self {
+        let rows = BOARD_ROWS.max(game.height());
+        let board = (0..rows).map(|y| (0..game.width()).map(|x| {
+            let filled = y < game.height() && game.pile.contains((game.height() - 1 - y, x));
+            if filled { Some('G') } else { None }
+        }).collect()).collect();
+
+        FrontendMessage::Start {
+            // the spare is never empty, but once it is used it can't be taken again until
+            // the next piece, to the bot there is no hold then
+            hold: (!game.spare_used).then(|| game.spare_piece.piece_type.letter()),
+            queue: std::iter::once(&game.current_piece).chain(game.next_pieces.iter())
+                .map(|piece| piece.piece_type.letter()).collect(),
+            combo: game.stats.combo,
+            back_to_back: game.stats.back_to_back,
+            board,
+        }
+    }
+}
+
+fn protocol_error(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+// an external bot running as a child process
+pub struct TbpBot {
+    pub name: String,
+    child: Child,
+    stdin: ChildStdin,
+    // lines of the bot's stdout, read on their own thread so a bot that hangs can be
+    // given up on
+    lines: Receiver<io::Result<String>>,
+    pub timeout: Duration,
+    // the bot has been given a game and is playing it
+    started: bool,
+    // pieces placed in the game when the bot's last suggestion was played, the
+    // bot gets the whole game again when it turns out to be out of date
+    synced_at: Option<usize>,
+}
+
+impl TbpBot {
+    // the command is split on whitespace, the first word is the program
+    pub fn spawn(command: &str) -> io::Result<Self> {
+        let mut words = command.split_whitespace();
+        let program = words.next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "empty bot command"))?;
+        let mut child = Command::new(program).args(words)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            // anything the bot prints besides messages would end up on the board
+            .stderr(Stdio::null())
+            .spawn()?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = BufReader::new(child.stdout.take().expect("stdout is piped"));
+        let (sender, lines) = mpsc::channel();
+        // ends when the bot exits, or when nobody is listening anymore
+        thread::spawn(move || {
+            for line in stdout.lines() {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut bot = TbpBot {
+            name: String::new(), child, stdin, lines, timeout: REPLY_TIMEOUT, started: false,
+            synced_at: None,
+        };
+        match bot.receive()? {
+            BotMessage::Info { name, .. } => bot.name = name,
+            other => return Err(protocol_error(format!("expected info, got {:?}", other))),
+        }
+        bot.send(&FrontendMessage::Rules { randomizer: "unknown".to_string() })?;
+        match bot.receive()? {
+            BotMessage::Ready => Ok(bot),
+            BotMessage::Error { reason } =>
+                Err(protocol_error(format!("the bot refused the rules: {}", reason))),
+            other => Err(protocol_error(format!("expected ready, got {:?}", other))),
+        }
+    }
+
+    fn send(&mut self, message: &FrontendMessage) -> io::Result<()> {
+        serde_json::to_writer(&mut self.stdin, message)?;
+        self.stdin.write_all(b"\n")?;
+        self.stdin.flush()
+    }
+
+    fn receive(&mut self) -> io::Result<BotMessage> {
+        loop {
+            let line = match self.lines.recv_timeout(self.timeout) {
+                Ok(line) => line?,
+                Err(RecvTimeoutError::Timeout) => return Err(io::Error::new(
+                    io::ErrorKind::TimedOut, "the bot took too long to answer")),
+                Err(RecvTimeoutError::Disconnected) =>
+                    return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "the bot exited")),
+            };
+            match serde_json::from_str(&line)? {
+                BotMessage::Unknown => continue,
+                message => return Ok(message),
+            }
+        }
+    }
+
+    // the inputs that put the piece where the bot wants it, if our moves can get there
+    fn resolve(game: &Tetris, play: &Move) -> Option<Plan> {
+        let piece_type = PieceType::from_letter(play.location.piece)?;
+        let target = play.location.placement(game.width(), game.height())?;
+        bot::candidates(game).into_iter().find(|plan| {
+            plan.piece.piece_type == piece_type && finesse::placement(&plan.piece) == target
+        })
+    }
+}
+
+impl Player for TbpBot {
+    fn plan(&mut self, game: &Tetris) -> io::Result<Option<Plan>> {
+        match self.synced_at.take() {
+            // the last suggestion was played, one more piece showed up in the queue
+            Some(pieces) if pieces + 1 == game.stats.pieces => {
+                if let Some(piece) = game.next_pieces.back() {
+                    self.send(&FrontendMessage::NewPiece { piece: piece.piece_type.letter() })?;
+                }
+            }
+            _ => {
+                if self.started {
+                    self.send(&FrontendMessage::Stop)?;
+                }
+                self.send(&FrontendMessage::start(game))?;
+                self.started = true;
+            }
+        }
+
+        self.send(&FrontendMessage::Suggest)?;
+        let moves = match self.receive()? {
+            BotMessage::Suggestion { moves } => moves,
+            BotMessage::Error { reason } => return Err(protocol_error(reason)),
+            other => return Err(protocol_error(format!("expected a suggestion, got {:?}", other))),
+        };
+
+        for play in moves {
+            if let Some(plan) = Self::resolve(game, &play) {
+                self.send(&FrontendMessage::Play { play })?;
+                self.synced_at = Some(game.stats.pieces);
+                return Ok(Some(plan));
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl Drop for TbpBot {
+    fn drop(&mut self) {
+        if self.send(&FrontendMessage::Quit).is_ok() {
+            let told = Instant::now();
+            while told.elapsed() < QUIT_TIMEOUT {
+                if !matches!(self.child.try_wait(), Ok(None)) {
+                    return;
+                }
+                thread::sleep(Duration::from_millis(10));
+            }
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
@@ -0,0 +1,222 @@
+// the terminal the game is drawn on and read from. The cargo features pick the
+// library behind it, the rest of the game only sees tui's Backend and our own Key.
+
+use std::io;
+
+use tui::Terminal;
+
+use crate::input::Key;
+
+#[cfg(not(any(feature = "termion", feature = "crossterm")))]
+compile_error!("enable either the termion or the crossterm feature");
+
+#[cfg(all(feature = "termion", not(feature = "crossterm")))]
+mod backend {
+    use std::io::{self, Stdout, Write};
+    use std::sync::{Mutex, MutexGuard};
+
+    use termion::event;
+    use termion::input::TermRead;
+    use termion::raw::{IntoRawMode, RawTerminal};
+    use termion::screen::{ToAlternateScreen, ToMainScreen};
+    use tui::backend::TermionBackend;
+
+    use crate::input::Key;
+
+    // turns raw mode off when dropped. It lives here rather than in the writer, which is
+    // out of reach inside tui's backend, so that a suspend can get to it.
+    static RAW: Mutex<Option<RawTerminal<Stdout>>> = Mutex::new(None);
+
+    fn raw() -> MutexGuard<'static, Option<RawTerminal<Stdout>>> {
+        RAW.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    // stdout on the alternate screen, back on the main screen and out of raw mode when
+    // it is dropped
+    pub struct Screen(Stdout);
+
+    impl Write for Screen {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.0.flush()
+        }
+    }
+
+    impl Drop for Screen {
+        fn drop(&mut self) {
+            let _ = write!(self.0, "{}", ToMainScreen);
+            let _ = self.0.flush();
+            raw().take();
+        }
+    }
+
+    pub type Backend = TermionBackend<Screen>;
+
+    pub fn backend() -> io::Result<Backend> {
+        *raw() = Some(io::stdout().into_raw_mode()?);
+        enter()?;
+        Ok(TermionBackend::new(Screen(io::stdout())))
+    }
+
+    pub fn leave() -> io::Result<()> {
+        let mut stdout = io::stdout();
+        write!(stdout, "{}", ToMainScreen)?;
+        stdout.flush()?;
+        raw().as_ref().map_or(Ok(()), RawTerminal::suspend_raw_mode)
+    }
+
+    pub fn enter() -> io::Result<()> {
+        raw().as_ref().map_or(Ok(()), RawTerminal::activate_raw_mode)?;
+        let mut stdout = io::stdout();
+        write!(stdout, "{}", ToAlternateScreen)?;
+        stdout.flush()
+    }
+
+    fn key(key: event::Key) -> Option<Key> {
+        Some(match key {
+            event::Key::Char(c) => Key::Char(c),
+            event::Key::Alt(c) => Key::Alt(c),
+            event::Key::Ctrl(c) => Key::Ctrl(c),
+            event::Key::F(n) => Key::F(n),
+            event::Key::Left => Key::Left,
+            event::Key::Right => Key::Right,
+            event::Key::Up => Key::Up,
+            event::Key::Down => Key::Down,
+            event::Key::Backspace => Key::Backspace,
+            event::Key::Home => Key::Home,
+            event::Key::End => Key::End,
+            event::Key::PageUp => Key::PageUp,
+            event::Key::PageDown => Key::PageDown,
+            event::Key::BackTab => Key::BackTab,
+            event::Key::Delete => Key::Delete,
+            event::Key::Insert => Key::Insert,
+            event::Key::Esc => Key::Esc,
+            _ => return None,
+        })
+    }
+
+    pub fn keys() -> impl Iterator<Item = Key> {
+        io::stdin().keys().flatten().filter_map(key)
+    }
+}
+
+#[cfg(feature = "crossterm")]
+mod backend {
+    use std::io::{self, Stdout, Write};
+
+    use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+    use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+    use tui::backend::CrosstermBackend;
+
+    use crate::input::Key;
+
+    // stdout in raw mode on the alternate screen, both undone when it is dropped
+    // the way termion does it
+    pub struct Screen(Stdout);
+
+    impl Write for Screen {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.0.flush()
+        }
+    }
+
+    impl Drop for Screen {
+        fn drop(&mut self) {
+            let _ = leave();
+        }
+    }
+
+    pub type Backend = CrosstermBackend<Screen>;
+
+    pub fn backend() -> io::Result<Backend> {
+        enter()?;
+        Ok(CrosstermBackend::new(Screen(io::stdout())))
+    }
+
+    pub fn leave() -> io::Result<()> {
+        crossterm::execute!(io::stdout(), LeaveAlternateScreen).map_err(io::Error::other)?;
+        terminal::disable_raw_mode().map_err(io::Error::other)
+    }
+
+    pub fn enter() -> io::Result<()> {
+        terminal::enable_raw_mode().map_err(io::Error::other)?;
+        crossterm::execute!(io::stdout(), EnterAlternateScreen).map_err(io::Error::other)
+    }
+
+    fn key(event: KeyEvent) -> Option<Key> {
+        let ctrl = event.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = event.modifiers.contains(KeyModifiers::ALT);
+        Some(match event.code {
+            KeyCode::Char(c) if ctrl => Key::Ctrl(c),
+            KeyCode::Char(c) if alt => Key::Alt(c),
+            KeyCode::Char(c) => Key::Char(c),
+            KeyCode::Enter => Key::Char('\n'),
+            KeyCode::Tab => Key::Char('\t'),
+            KeyCode::F(n) => Key::F(n),
+            KeyCode::Left => Key::Left,
+            KeyCode::Right => Key::Right,
+            KeyCode::Up => Key::Up,
+            KeyCode::Down => Key::Down,
+            KeyCode::Backspace => Key::Backspace,
+            KeyCode::Home => Key::Home,
+            KeyCode::End => Key::End,
+            KeyCode::PageUp => Key::PageUp,
+            KeyCode::PageDown => Key::PageDown,
+            KeyCode::BackTab => Key::BackTab,
+            KeyCode::Delete => Key::Delete,
+            KeyCode::Insert => Key::Insert,
+            KeyCode::Esc => Key::Esc,
+            KeyCode::Null => return None,
+        })
+    }
+
+    pub fn keys() -> impl Iterator<Item = Key> {
+        std::iter::from_fn(|| loop {
+            match event::read() {
+                Ok(Event::Key(event)) => match key(event) {
+                    Some(key) => return Some(key),
+                    None => continue,
+                },
+                Ok(_) => continue,
+                Err(_) => return None,
+            }
+        })
+    }
+}
+
+pub use backend::Backend;
+
+pub type Term = Terminal<Backend>;
+
+// switches the terminal to raw mode and the alternate screen, until the returned
+// terminal is dropped
+pub fn open() -> io::Result<Term> {
+    Terminal::new(backend::backend()?)
+}
+
+// the keys pressed on the terminal, as they come
+pub fn keys() -> impl Iterator<Item = Key> {
+    backend::keys()
+}
+
+// hands the terminal back to the shell and stops the program until it is continued,
+// then takes the terminal again. Everything has to be drawn anew after it.
+#[cfg(unix)]
+pub fn suspend() -> io::Result<()> {
+    backend::leave()?;
+    signal_hook::low_level::raise(signal_hook::consts::SIGSTOP)?;
+    backend::enter()
+}
+
+// there is no job control to hand the terminal over to
+#[cfg(not(unix))]
+pub fn suspend() -> io::Result<()> {
+    Ok(())
+}
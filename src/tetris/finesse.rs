@@ -0,0 +1,98 @@
+use std::collections::{BTreeMap, HashSet, VecDeque};
+
+use crate::piece::Piece;
+use super::{Input, Tetris};
+
+// the cells a piece ends up in, sorted so pieces that look the same compare equal
+pub type Placement = [(usize, usize); 4];
+
+pub fn placement(piece: &Piece) -> Placement {
+    let mut cells = piece.get_positions();
+    cells.sort_unstable();
+    cells
+}
+
+// the cheapest way found to a placement, piece is where it ends up after the hard drop
+#[derive(Clone)]
+pub struct Route {
+    pub inputs: usize,
+    pub piece: Piece,
+}
+
+// the fewest inputs needed to hard drop a piece on every placement it can reach
+// from spawn without soft dropping. Holding a move key until the wall counts as
+// a single input, the hard drop itself isn't counted.
+pub fn min_inputs(game: &Tetris, spawn: &Piece) -> BTreeMap<Placement, Route> {
+    let mut routes = BTreeMap::new();
+    let mut seen = HashSet::new();
+    let mut queue = VecDeque::new();
+    seen.insert((spawn.anchor_x, spawn.anchor_y, spawn.rotation));
+    queue.push_back((spawn.clone(), 0));
+
+    // breadth first, so the first route to a placement is one of the shortest
+    while let Some((piece, inputs)) = queue.pop_front() {
+        let dropped = game.dropped(&piece);
+        routes.entry(placement(&dropped)).or_insert(Route { inputs, piece: dropped });
+
+        let next = [
+            game.shifted(&piece, -1),
+            game.shifted(&piece, 1),
+            game.shifted_to_wall(&piece, -1),
+            game.shifted_to_wall(&piece, 1),
+            game.rotated(&piece, 1),
+            game.rotated(&piece, 3),
+            game.rotated(&piece, 2),
+        ];
+        for piece in next.iter().flatten() {
+            if seen.insert((piece.anchor_x, piece.anchor_y, piece.rotation)) {
+                queue.push_back((piece.clone(), inputs + 1));
+            }
+        }
+    }
+    routes
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Verdict {
+    Clean,
+    // the piece took more inputs than it had to
+    Fault { used: usize, needed: usize },
+}
+
+// the inputs the player spent on the current piece since it spawned
+#[derive(Clone)]
+pub struct Tracker {
+    pub spawn: Piece,
+    pub inputs: usize,
+    // soft dropping can reach places finesse doesn't know about, so it isn't judged
+    pub soft_dropped: bool,
+}
+
+impl Tracker {
+    pub fn new(spawn: &Piece) -> Self {
+        Tracker { spawn: spawn.clone(), inputs: 0, soft_dropped: false }
+    }
+
+    // only fresh key presses get here, a held key counts once
+    pub fn on_input(&mut self, input: Input) {
+        match input {
+            Input::MoveLeft | Input::MoveRight
+                | Input::RotateCw | Input::RotateCcw | Input::Rotate180 => self.inputs += 1,
+            Input::SoftDrop => self.soft_dropped = true,
+            Input::HardDrop | Input::Hold | Input::Gravity => (),
+        }
+    }
+
+    // None when the placement can't be judged, like tucks and spins
+    pub fn judge(&self, game: &Tetris, locked: &Piece) -> Option<Verdict> {
+        if self.soft_dropped {
+            return None;
+        }
+        let needed = min_inputs(game, &self.spawn).get(&placement(locked))?.inputs;
+        if self.inputs > needed {
+            Some(Verdict::Fault { used: self.inputs, needed })
+        } else {
+            Some(Verdict::Clean)
+        }
+    }
+}
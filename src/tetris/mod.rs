@@ -1,15 +1,98 @@
 use crate::pile::Pile;
-use crate::piece::Piece;
-use crate::config;
+use crate::piece::{Piece, PieceType};
+use crate::config::Rules;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use std::collections::VecDeque;
 
 use delegate::delegate;
 
+pub mod finesse;
+pub mod mode;
+pub mod movegen;
+pub mod rotation;
+pub mod stats;
 mod time_manager;
 use time_manager::TimeManager;
+pub use finesse::{Route, Verdict};
+pub use mode::{Mode, Outcome};
+pub use rotation::RotationSystem;
+pub use stats::Stats;
+
+// everything that changes the state of the game, as opposed to UI actions
+// like pausing. Replaying the same inputs on the same seed gives the same game.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Input {
+    MoveLeft,
+    MoveRight,
+    SoftDrop,
+    HardDrop,
+    RotateCw,
+    RotateCcw,
+    Rotate180,
+    Hold,
+    // the piece falling on its own
+    Gravity,
+}
+
+impl Input {
+    // the inputs that are kept for the next piece when they come between two pieces
+    pub fn modifies_spawn(self) -> bool {
+        matches!(self, Input::RotateCw | Input::RotateCcw | Input::Rotate180 | Input::Hold)
+    }
+}
+
+// what the next piece does as it comes in: initial rotation and initial hold (IRS and IHS)
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct Spawn {
+    // clockwise
+    pub quarter_turns: u8,
+    pub hold: bool,
+}
+
+// a level is this many cleared lines, the gravity gets faster with every line anyway
+pub const LINES_PER_LEVEL: usize = 10;
+// events nobody takes are dropped after this many
+const KEPT_EVENTS: usize = 16;
+
+// the time between a piece locking and the next one coming in, it ends at until_ms
+// of the game clock
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Delay {
+    // the full rows are still on the pile
+    LineClear { rows: Vec<usize>, until_ms: u64 },
+    // entry delay (ARE), the pile is settled and the next piece isn't out yet
+    Entry { until_ms: u64 },
+}
+
+impl Delay {
+    pub fn until_ms(&self) -> u64 {
+        match self {
+            Delay::LineClear { until_ms, .. } | Delay::Entry { until_ms } => *until_ms,
+        }
+    }
+}
+
+// what happened in the game, for the UI to animate
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum GameEvent {
+    // a piece went on the pile, its cells that stay after the clear. They are moved down by
+    // the lines cleared under them when those go right away, with a line clear delay the
+    // lines are still on the board and the cells are where the piece locked.
+    Locked { cells: Vec<(usize, usize)> },
+    // rows numbered as they were before being cleared, they stay on the board for
+    // delay_ms. Also sent for a T-spin that clears nothing.
+    Cleared { rows: Vec<usize>, t_spin: bool, back_to_back: bool, points: usize, delay_ms: u64 },
+    LevelUp { level: usize },
+}
 
 pub struct Tetris {
     pub current_piece: Piece,
-    pub next_piece: Piece,
+    pub next_pieces: VecDeque<Piece>,
     pub spare_piece: Piece,
     pub projected_piece: Piece,
     pub pile: Pile,
@@ -17,20 +100,107 @@ pub struct Tetris {
     pub spare_used: bool,
     pub score: usize,
     pub last_combo: usize,
-    
+    pub lines: usize,
+    pub stats: Stats,
+    // whether the last thing that moved the piece was a rotation, for T-spins
+    last_move_rotated: bool,
+    finesse: finesse::Tracker,
+    // how the last placed piece did on finesse, None if it couldn't be judged
+    pub last_verdict: Option<Verdict>,
+    // finesse is judged on every lock for the player to see, games nobody watches can turn
+    // it off. The trainer always judges.
+    pub judge_finesse: bool,
+    // where the piece has to go in the trainer
+    pub target: Option<Route>,
+    events: VecDeque<GameEvent>,
+    // set while there is no piece in play, the current piece is the one that locked
+    pub delay: Option<Delay>,
+    // the rotations and holds asked for during the delay
+    pub spawn: Spawn,
+    line_clear_delay_ms: u64,
+    entry_delay_ms: u64,
+
+    pub mode: Mode,
+    pub rotation_system: RotationSystem,
+    pub seed: u64,
+    rng: StdRng,
     time_manager: TimeManager,
 }
 
 impl Tetris {
+    pub fn apply(&mut self, input: Input) {
+        // a piece that is due comes in first and gets the input
+        self.update();
+        if self.delay.is_some() {
+            // nothing moves until the next piece is out
+            if input.modifies_spawn() {
+                self.keep_for_spawn(input);
+            }
+            return;
+        }
+        if input != Input::Gravity {
+            self.stats.keys += 1;
+        }
+        self.finesse.on_input(input);
+        self.apply_repeat(input);
+    }
+
+    // a rotation or hold kept for the next piece, it comes in at the next update with all
+    // of them. Rotations add up, a turn back undoes one.
+    pub fn keep_for_spawn(&mut self, input: Input) {
+        self.stats.keys += 1;
+        let quarter_turns = match input {
+            Input::RotateCw => 1,
+            Input::RotateCcw => 3,
+            Input::Rotate180 => 2,
+            _ => {
+                self.spawn.hold = true;
+                return;
+            }
+        };
+        self.spawn.quarter_turns = (self.spawn.quarter_turns + quarter_turns) % 4;
+    }
+
+    // an input coming from a key that is held down, it isn't counted as a key press
+    pub fn apply_repeat(&mut self, input: Input) {
+        self.update();
+        if self.delay.is_some() {
+            return;
+        }
+        match input {
+            Input::MoveLeft => self.move_left(),
+            Input::MoveRight => self.move_right(),
+            Input::SoftDrop => self.move_down(),
+            Input::HardDrop => self.drop_to_bottom(),
+            Input::RotateCw => self.safe_rotate_clockwise(),
+            Input::RotateCcw => self.safe_rotate_counter_clockwise(),
+            Input::Rotate180 => self.safe_rotate_180(),
+            Input::Hold => self.use_spare(),
+            Input::Gravity => self.gravity_step(),
+        }
+    }
+
+    // called whenever the tick timeout runs out
+    pub fn gravity_step(&mut self) {
+        if self.can_move_down() {
+            self.move_down();
+        } else if self.should_finish_turn() {
+            self.finish_turn();
+        } else {
+            self.advance_stuck();
+        }
+    }
+
     pub fn drop_to_bottom (&mut self) {
         while self.can_move_down() {
             self.current_piece.move_down_unsafe();
+            self.last_move_rotated = false;
         }
         self.finish_turn();
     }
 
     // TODO: make a macro that marks methods that should finish with project
-    fn project(&mut self) {
+    pub fn project(&mut self) {
         self.projected_piece = self.current_piece.clone();
         while !self.touches_on_bottom(&self.projected_piece) {
             self.projected_piece.move_down_unsafe();
@@ -41,9 +211,31 @@ impl Tetris {
         !self.touches_on_bottom(&self.current_piece)
     }
 
-    fn put_in_starting_position(&mut self) {
-        self.current_piece.place_at(
-            (config::LEFT_THRESHOLD + config::RIGHT_THRESHOLD) as i16 / 2 - 2, 0);
+    // a piece held as it comes in is swapped right away, a rotation that doesn't fit
+    // is left out
+    fn put_in_starting_position(&mut self, spawn: Spawn) {
+        if spawn.hold && !self.spare_used {
+            self.spare_used = true;
+            self.current_piece.swap_figures(&mut self.spare_piece);
+            self.spare_piece.refresh();
+        }
+        self.current_piece.place_at(self.width() as i16 / 2 - 2, 0);
+        if spawn.quarter_turns > 0 {
+            if let Some(rotated) = self.rotated(&self.current_piece, spawn.quarter_turns) {
+                self.current_piece = rotated;
+            }
+        }
+    }
+
+    // the piece that comes into play on a hold, None when hold was already used
+    pub fn held_piece(&self) -> Option<Piece> {
+        if self.spare_used {
+            return None;
+        }
+        let mut piece = self.current_piece.clone();
+        piece.swap_figures(&mut self.spare_piece.clone());
+        piece.place_at(self.width() as i16 / 2 - 2, 0);
+        Some(piece)
     }
     
     pub fn use_spare (&mut self) {
@@ -52,31 +244,117 @@ impl Tetris {
 
             self.current_piece.swap_figures(&mut self.spare_piece);
             self.spare_piece.refresh();
-            self.put_in_starting_position();
-            self.project();
+            self.put_in_starting_position(Spawn::default());
+            self.on_spawn();
         }
     }
 
-    pub fn finish_turn (&mut self) -> bool {
-        if self.collides(&self.current_piece, (0, 0)) {
-            return true;
+    // a new piece is in its starting position
+    fn on_spawn(&mut self) {
+        self.finesse = finesse::Tracker::new(&self.current_piece);
+        if self.mode == Mode::Trainer {
+            let routes = finesse::min_inputs(self, &self.current_piece);
+            let idx = self.rng.gen_range(0, routes.len());
+            self.target = routes.into_iter().nth(idx).map(|(_, route)| route);
         }
+        self.project();
+    }
 
-        self.pile.add(&self.current_piece);
-        let cleaned_up = self.pile.cleanup_full_lines();
+    // the trainer never keeps a piece, it either moves on to the next one or
+    // puts the same piece back at the start when it missed the target or took
+    // too many inputs getting there
+    fn finish_training(&mut self, verdict: Option<Verdict>) {
+        let on_target = self.target.as_ref().map(|route| finesse::placement(&route.piece))
+            == Some(finesse::placement(&self.current_piece));
+
+        if on_target && verdict == Some(Verdict::Clean) {
+            self.stats.on_lock(self.current_piece.piece_type, 0, false);
+            self.score += 1;
+            let cells = self.current_piece.get_positions().to_vec();
+            self.emit(GameEvent::Locked { cells });
+            self.spawn_next();
+        } else {
+            self.current_piece = self.finesse.spawn.clone();
+            self.finesse = finesse::Tracker::new(&self.current_piece);
+            self.last_move_rotated = false;
+            self.project();
+        }
+        self.time_manager.tick();
+    }
 
-        self.current_piece.swap_figures(&mut self.next_piece);
-        self.next_piece.randomize();
+    fn spawn_next(&mut self) {
+        let mut next_piece = self.next_pieces.pop_front()
+            .expect("the preview queue is never empty");
+        self.current_piece.swap_figures(&mut next_piece);
+        next_piece.randomize(&mut self.rng);
+        self.next_pieces.push_back(next_piece);
 
-        self.put_in_starting_position();
+        self.spare_used = false;
+        let spawn = std::mem::take(&mut self.spawn);
+        self.put_in_starting_position(spawn);
         // try fuzzy fitting when the piece just appears
         let mut temp = self.current_piece.clone();
         if self.try_fuzzy_fit(&mut temp) {
             self.current_piece = temp;
         }
 
-        self.spare_used = false;
-        self.score += cleaned_up * config::RIGHT_THRESHOLD;
+        self.on_spawn();
+    }
+
+    pub fn finish_turn (&mut self) -> bool {
+        if self.collides(&self.current_piece, (0, 0)) {
+            return true;
+        }
+
+        let verdict = if self.judge_finesse || self.mode == Mode::Trainer {
+            self.finesse.judge(self, &self.current_piece)
+        } else {
+            None
+        };
+        self.last_verdict = verdict;
+        self.stats.on_finesse(verdict);
+        if self.mode == Mode::Trainer {
+            self.finish_training(verdict);
+            return false;
+        }
+
+        let t_spin = self.is_t_spin();
+        self.pile.add(&self.current_piece);
+        let full_lines = self.pile.full_lines();
+        let cleaned_up = full_lines.len();
+        let clear_delay = if cleaned_up > 0 { self.line_clear_delay_ms } else { 0 };
+        // without a delay the lines go right away
+        let collapsed: &[usize] = if clear_delay == 0 {
+            self.pile.cleanup_full_lines();
+            &full_lines
+        } else {
+            &[]
+        };
+        let back_to_back = self.stats.back_to_back && cleaned_up > 0
+            && (t_spin || cleaned_up >= 4);
+        self.stats.on_lock(self.current_piece.piece_type, cleaned_up, t_spin);
+        self.last_move_rotated = false;
+
+        // the cells of the piece that stay, moved down by the lines cleared under them
+        let cells = self.current_piece.get_positions().iter()
+            .filter(|(i, _)| !full_lines.contains(i))
+            .map(|&(i, j)| (i + collapsed.iter().filter(|&&row| row > i).count(), j))
+            .collect();
+        self.emit(GameEvent::Locked { cells });
+
+        let now = self.elapsed_ms();
+        self.delay = match (clear_delay, self.entry_delay_ms) {
+            (0, 0) => None,
+            (0, entry) => Some(Delay::Entry { until_ms: now + entry }),
+            (clear, _) => Some(Delay::LineClear { rows: full_lines.clone(),
+                                                  until_ms: now + clear }),
+        };
+        if self.delay.is_none() {
+            self.spawn_next();
+        }
+        let (score, level) = (self.score, self.level());
+        self.lines += cleaned_up;
+        self.score += cleaned_up * self.width();
 
         if cleaned_up > 1 {
             // for combos
@@ -84,17 +362,90 @@ impl Tetris {
             self.last_combo = cleaned_up;
         }
 
+        if cleaned_up > 0 || t_spin {
+            let points = self.score - score;
+            self.emit(GameEvent::Cleared { rows: full_lines, t_spin, back_to_back, points,
+                                           delay_ms: clear_delay });
+        }
+        if self.level() > level {
+            self.emit(GameEvent::LevelUp { level: self.level() });
+        }
+
         self.time_manager.update_tick_speed(cleaned_up);
         self.time_manager.tick();
-        self.project();
-        return false;
+        false
+    }
+
+    // ends the delays that are over by the game clock, the lines fall and the next
+    // piece comes in. Each delay starts where the one before ended, whenever this is
+    // called, so replays come out the same.
+    pub fn update(&mut self) {
+        let now = self.elapsed_ms();
+        while let Some(delay) = self.delay.take() {
+            if now < delay.until_ms() {
+                self.delay = Some(delay);
+                return;
+            }
+            if let Delay::LineClear { until_ms, .. } = delay {
+                self.pile.cleanup_full_lines();
+                if self.entry_delay_ms > 0 {
+                    self.delay = Some(Delay::Entry { until_ms: until_ms + self.entry_delay_ms });
+                    continue;
+                }
+            }
+            self.spawn_next();
+            self.time_manager.tick();
+        }
+    }
+
+    // when the next piece comes in by the game clock, if it isn't in yet
+    pub fn spawn_at_ms(&self) -> Option<u64> {
+        match self.delay.as_ref()? {
+            Delay::LineClear { until_ms, .. } => Some(until_ms + self.entry_delay_ms),
+            Delay::Entry { until_ms } => Some(*until_ms),
+        }
+    }
+
+    // for owners of the clock that don't wait, like simulations: moves the clock to the
+    // end of the delays
+    pub fn skip_delays(&mut self) {
+        while let Some(until_ms) = self.delay.as_ref().map(Delay::until_ms) {
+            self.set_elapsed_ms(until_ms.max(self.elapsed_ms()));
+            self.update();
+        }
+    }
+
+    fn emit(&mut self, event: GameEvent) {
+        if self.events.len() == KEPT_EVENTS {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
+    }
+
+    // the events since they were last taken, oldest first
+    pub fn take_events(&mut self) -> impl Iterator<Item = GameEvent> + '_ {
+        self.events.drain(..)
+    }
+
+    pub fn level(&self) -> usize {
+        self.lines / LINES_PER_LEVEL + 1
+    }
+
+    pub fn outcome(&self) -> Option<Outcome> {
+        if self.delay.is_none() && self.collides(&self.current_piece, (0,0)) {
+            Some(Outcome::ToppedOut)
+        } else if self.mode.goal_reached(self.lines, self.elapsed_ms()) {
+            Some(Outcome::Completed)
+        } else {
+            None
+        }
     }
 
     pub fn is_over (&self) -> bool {
-        self.collides(&self.current_piece, (0,0))
+        self.outcome().is_some()
     }
 
-    fn collides(&self, piece: &Piece, (offset_x, offset_y): (i16, i16)) -> bool {
+    pub fn collides(&self, piece: &Piece, (offset_x, offset_y): (i16, i16)) -> bool {
         for (i, j) in piece.get_positions_unsafe().iter() {
             let real_i = match *i + offset_y {
                 x if x < 0 => return true,
@@ -106,14 +457,34 @@ impl Tetris {
                 x => x
             } as usize;
 
-            if real_j >= config::RIGHT_THRESHOLD
-                || real_i >= config::BOTTOM_THRESHOLD
+            if real_j >= self.width()
+                || real_i >= self.height()
                 || self.pile.contains((real_i, real_j)) {
                 return true;
             }
         }
 
-        return false;
+        false
+    }
+
+    // three corner rule: a T that got into place by rotating, with at least
+    // three of the four cells diagonal to its center taken
+    fn is_t_spin(&self) -> bool {
+        self.is_t_spin_at(&self.current_piece, self.last_move_rotated)
+    }
+
+    fn is_t_spin_at(&self, piece: &Piece, rotated: bool) -> bool {
+        if piece.piece_type != PieceType::T || !rotated {
+            return false;
+        }
+
+        let (center_i, center_j) = (piece.anchor_y + 1, piece.anchor_x + 1);
+        let taken = [(-1, -1), (-1, 1), (1, -1), (1, 1)].iter().filter(|(di, dj)| {
+            let (i, j) = (center_i + di, center_j + dj);
+            i < 0 || j < 0 || j as usize >= self.width() || i as usize >= self.height()
+                || self.pile.contains((i as usize, j as usize))
+        }).count();
+        taken >= 3
     }
 
     fn touches_on_bottom(&self, piece: &Piece) -> bool {
@@ -123,6 +494,7 @@ impl Tetris {
     pub fn move_left(&mut self) {
         if !self.collides(&self.current_piece, (-1, 0)) {
             self.current_piece.move_left_unsafe();
+            self.last_move_rotated = false;
             self.project();
         }
     }
@@ -130,6 +502,7 @@ impl Tetris {
     pub fn move_right(&mut self) {
         if !self.collides(&self.current_piece, (1, 0)) {
             self.current_piece.move_right_unsafe();
+            self.last_move_rotated = false;
             self.project();
         }
     }
@@ -139,86 +512,188 @@ impl Tetris {
         if !self.touches_on_bottom(&self.current_piece) {
             self.time_manager.tick();
             self.current_piece.move_down_unsafe();
+            self.last_move_rotated = false;
         }
     }
 
     pub fn safe_rotate_clockwise(&mut self) {
-        self.safe_rotate_internal(true);
+        self.safe_rotate_internal(1);
     }
 
     pub fn safe_rotate_counter_clockwise(&mut self) {
-        self.safe_rotate_internal(false);
+        self.safe_rotate_internal(3);
+    }
+
+    pub fn safe_rotate_180(&mut self) {
+        self.safe_rotate_internal(2);
     }
 
     fn try_fuzzy_fit(&self, piece: &mut Piece) -> bool {
-        if !self.collides(&piece, (0, 0)) {
+        if !self.collides(piece, (0, 0)) {
             return true;
         }
         // move 1 to the left from the initial position
-        if !self.collides(&piece, (-1, 0)) {
+        if !self.collides(piece, (-1, 0)) {
             piece.move_left_unsafe();
             return true;
         }
         // move 1 to the right from the initial position
-        if !self.collides(&piece, (1, 0)) {
+        if !self.collides(piece, (1, 0)) {
             piece.move_right_unsafe();
             return true;
         }
         // move 2 to the left from the initial position
-        if !self.collides(&piece, (-2, 0)) {
+        if !self.collides(piece, (-2, 0)) {
             piece.move_left_unsafe();
             piece.move_left_unsafe();
             return true;
         }
         // move 2 to the right from the initial position
-        if !self.collides(&piece, (2, 0)) {
+        if !self.collides(piece, (2, 0)) {
             piece.move_right_unsafe();
             piece.move_right_unsafe();
             return true;
         }
-        return false;
+        false
     }
 
-    fn safe_rotate_internal (&mut self, clockwise: bool) {
-        let mut temp = self.current_piece.clone();
-        if clockwise {
+    // quarter_turns are clockwise
+    fn safe_rotate_internal (&mut self, quarter_turns: u8) {
+        if let Some(rotated) = self.rotated(&self.current_piece, quarter_turns) {
+            self.current_piece = rotated;
+            self.last_move_rotated = true;
+            self.project();
+        }
+    }
+
+    // where a piece ends up after rotating, kicks included, if it can rotate at all
+    pub fn rotated(&self, piece: &Piece, quarter_turns: u8) -> Option<Piece> {
+        let mut temp = piece.clone();
+        for _ in 0..quarter_turns {
             temp.rotate_clockwise();
-        } else {
-            temp.rotate_counter_clockwise();
         }
 
-        if self.try_fuzzy_fit(&mut temp) {
-            self.current_piece = temp;
-            self.project();
+        let kicks = self.rotation_system.kicks(temp.piece_type, piece.rotation, temp.rotation);
+        let (x, y) = kicks.iter().find(|offset| !self.collides(&temp, **offset))?;
+        temp.place_at(temp.anchor_x + x, temp.anchor_y + y);
+        Some(temp)
+    }
+
+    pub fn shifted(&self, piece: &Piece, dx: i16) -> Option<Piece> {
+        if self.collides(piece, (dx, 0)) {
+            return None;
+        }
+        let mut temp = piece.clone();
+        temp.place_at(temp.anchor_x + dx, temp.anchor_y);
+        Some(temp)
+    }
+
+    // as far as the piece goes in one direction, None if it can't move at all
+    pub fn shifted_to_wall(&self, piece: &Piece, dx: i16) -> Option<Piece> {
+        let mut temp = self.shifted(piece, dx)?;
+        while let Some(next) = self.shifted(&temp, dx) {
+            temp = next;
+        }
+        Some(temp)
+    }
+
+    // one row lower, if there is room
+    pub fn fallen(&self, piece: &Piece) -> Option<Piece> {
+        if self.touches_on_bottom(piece) {
+            return None;
         }
+        let mut temp = piece.clone();
+        temp.move_down_unsafe();
+        Some(temp)
+    }
+
+    pub fn dropped(&self, piece: &Piece) -> Piece {
+        let mut temp = piece.clone();
+        while !self.touches_on_bottom(&temp) {
+            temp.move_down_unsafe();
+        }
+        temp
+    }
+
+    pub fn width(&self) -> usize {
+        self.pile.col_count
+    }
+
+    pub fn height(&self) -> usize {
+        self.pile.row_count
     }
 
     pub fn get_tick_speed(&self) -> usize {
         self.time_manager.tick_time
     }
 
+    // ms until something should happen on its own
+    pub fn get_timeout(&self) -> usize {
+        let timeout = match &self.delay {
+            Some(delay) => delay.until_ms().saturating_sub(self.elapsed_ms()) as usize,
+            None => self.time_manager.get_timeout(),
+        };
+        match self.mode.remaining_ms(self.elapsed_ms()) {
+            Some(remaining) => timeout.min(remaining as usize),
+            None => timeout,
+        }
+    }
+
+    pub fn elapsed_ms(&self) -> u64 {
+        self.time_manager.clock.elapsed_ms()
+    }
+
+    // stop following real time, the owner of the game sets the time from now on
+    pub fn set_elapsed_ms(&mut self, ms: u64) {
+        self.time_manager.clock.set_ms(ms);
+    }
+
+    pub fn follow_wall_clock(&mut self) {
+        self.time_manager.clock.switch_to_wall();
+    }
+
     delegate! {
         to self.time_manager {
-            pub fn get_timeout(&self) -> usize;
             pub fn should_finish_turn(&self) -> bool;
             pub fn advance_stuck(&mut self);
+            pub fn pause(&mut self);
+            pub fn resume(&mut self);
+            pub fn is_paused(&self) -> bool;
         }
     }
 
-    pub fn new() -> Self {
+    pub fn new(rules: &Rules, mode: Mode, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
         let mut tetris = Tetris {
-            pile: Pile::new(config::RIGHT_THRESHOLD, config::BOTTOM_THRESHOLD),
-            current_piece: Piece::new_random_piece_at(0, 0),
-            next_piece: Piece::new_random_piece_at(0, 1),
-            spare_piece: Piece::new_random_piece_at(0, 7),
-            projected_piece: Piece::new_random_piece_at(0, 0),
+            pile: Pile::new(rules.width, rules.height),
+            current_piece: Piece::new_random_piece_at(0, 0, &mut rng),
+            next_pieces: (0..rules.preview_count)
+                .map(|_| Piece::new_random_piece_at(0, 0, &mut rng)).collect(),
+            spare_piece: Piece::new_random_piece_at(0, 0, &mut rng),
+            projected_piece: Piece::new_random_piece_at(0, 0, &mut rng),
             spare_used: false,
             score: 0,
             last_combo: 0,
-            time_manager: TimeManager::new()
+            lines: 0,
+            stats: Stats::default(),
+            last_move_rotated: false,
+            finesse: finesse::Tracker::new(&Piece::new(PieceType::Square, 0, 0)),
+            last_verdict: None,
+            judge_finesse: true,
+            target: None,
+            events: VecDeque::new(),
+            delay: None,
+            spawn: Spawn::default(),
+            line_clear_delay_ms: rules.line_clear_delay_ms as u64,
+            entry_delay_ms: rules.entry_delay_ms as u64,
+            mode,
+            rotation_system: rules.rotation,
+            seed,
+            rng,
+            time_manager: TimeManager::new(rules)
         };
-        tetris.put_in_starting_position();
-        tetris.project();
+        tetris.put_in_starting_position(Spawn::default());
+        tetris.on_spawn();
         tetris
     }
 }
@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+
+use std::fmt;
+
+pub const SPRINT_LINES: usize = 40;
+pub const ULTRA_TIME_MS: u64 = 2 * 60 * 1000;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize,
+         clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    // endless, play until topping out
+    Marathon,
+    // clear 40 lines as fast as possible
+    Sprint,
+    // score as much as possible in 2 minutes
+    Ultra,
+    // single pieces to put on a marked spot with as few inputs as possible
+    Trainer,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Outcome {
+    ToppedOut,
+    // the goal of the mode has been reached
+    Completed,
+}
+
+impl Mode {
+    pub const ALL: [Mode; 4] = [Mode::Marathon, Mode::Sprint, Mode::Ultra, Mode::Trainer];
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Mode::Marathon => "Endless, play until you top out",
+            Mode::Sprint => "Clear 40 lines as fast as possible",
+            Mode::Ultra => "Score as much as possible in 2 minutes",
+            Mode::Trainer => "Place each piece on the marked spot with the fewest inputs",
+        }
+    }
+
+    pub fn goal_reached(&self, lines: usize, elapsed_ms: u64) -> bool {
+        match self {
+            Mode::Marathon | Mode::Trainer => false,
+            Mode::Sprint => lines >= SPRINT_LINES,
+            Mode::Ultra => elapsed_ms >= ULTRA_TIME_MS,
+        }
+    }
+
+    // how long until the mode ends on its own, for modes that have a time limit
+    pub fn remaining_ms(&self, elapsed_ms: u64) -> Option<u64> {
+        match self {
+            Mode::Ultra => Some(ULTRA_TIME_MS.saturating_sub(elapsed_ms)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Mode::Marathon => "Marathon",
+            Mode::Sprint => "Sprint",
+            Mode::Ultra => "Ultra",
+            Mode::Trainer => "Trainer",
+        })
+    }
+}
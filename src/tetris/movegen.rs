@@ -0,0 +1,91 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::piece::Piece;
+use super::{finesse, Input, Tetris};
+
+// one way of locking a piece
+#[derive(Clone)]
+pub struct Move {
+    // the piece where it locks
+    pub piece: Piece,
+    // one of the shortest input sequences from where the piece started, ending with the hard drop
+    pub inputs: Vec<Input>,
+    // the piece got there by rotating into a T-spin
+    pub t_spin: bool,
+}
+
+// where a piece is and whether it got there by rotating, which matters for T-spins
+type State = (i16, i16, u8, bool);
+
+fn state(piece: &Piece, rotated: bool) -> State {
+    (piece.anchor_x, piece.anchor_y, piece.rotation, rotated)
+}
+
+const INPUTS: [Input; 6] = [
+    Input::MoveLeft,
+    Input::MoveRight,
+    Input::SoftDrop,
+    Input::RotateCw,
+    Input::RotateCcw,
+    Input::Rotate180,
+];
+
+// every distinct placement the piece can lock in when moved, rotated and soft
+// dropped by the game's own rules, tucks and spins included. Gravity is left
+// out, the piece is assumed to stay up for as long as it takes.
+pub fn moves(game: &Tetris, piece: &Piece) -> Vec<Move> {
+    let start = state(piece, false);
+    // how each state was first reached, for walking the path back
+    let mut parents: HashMap<State, (State, Input)> = HashMap::new();
+    let mut seen = HashSet::new();
+    let mut queue = VecDeque::new();
+    seen.insert(start);
+    queue.push_back((piece.clone(), false));
+
+    let mut result = Vec::new();
+    let mut placed = HashSet::new();
+    // breadth first, so the first path to a placement is one of the shortest
+    while let Some((piece, rotated)) = queue.pop_front() {
+        let current = state(&piece, rotated);
+        let dropped = game.dropped(&piece);
+        // a hard drop that moves the piece loses the rotation
+        let t_spin = rotated && dropped.anchor_y == piece.anchor_y
+            && game.is_t_spin_at(&dropped, true);
+        if placed.insert((finesse::placement(&dropped), t_spin)) {
+            let mut inputs = path(&parents, start, current);
+            inputs.push(Input::HardDrop);
+            result.push(Move { piece: dropped, inputs, t_spin });
+        }
+
+        for input in INPUTS.iter() {
+            let next = match input {
+                Input::MoveLeft => game.shifted(&piece, -1).map(|piece| (piece, false)),
+                Input::MoveRight => game.shifted(&piece, 1).map(|piece| (piece, false)),
+                Input::SoftDrop => game.fallen(&piece).map(|piece| (piece, false)),
+                Input::RotateCw => game.rotated(&piece, 1).map(|piece| (piece, true)),
+                Input::RotateCcw => game.rotated(&piece, 3).map(|piece| (piece, true)),
+                _ => game.rotated(&piece, 2).map(|piece| (piece, true)),
+            };
+            if let Some((next, rotated)) = next {
+                let next_state = state(&next, rotated);
+                if seen.insert(next_state) {
+                    parents.insert(next_state, (current, *input));
+                    queue.push_back((next, rotated));
+                }
+            }
+        }
+    }
+    result
+}
+
+fn path(parents: &HashMap<State, (State, Input)>, start: State, end: State) -> Vec<Input> {
+    let mut inputs = Vec::new();
+    let mut current = end;
+    while current != start {
+        let (parent, input) = parents[&current];
+        inputs.push(input);
+        current = parent;
+    }
+    inputs.reverse();
+    inputs
+}
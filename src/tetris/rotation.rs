@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+
+use crate::piece::PieceType;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum RotationSystem {
+    // rotate in place, then nudge the piece up to two cells to either side
+    Classic,
+    // the guideline Super Rotation System
+    Srs,
+    // Arika (TGM) style: only try one cell to the right and then to the left,
+    // the straight piece never kicks
+    Ars,
+}
+
+// offsets are (x, y) with y growing downwards like the rest of the board
+type Kicks = &'static [(i16, i16)];
+
+static CLASSIC_KICKS: Kicks = &[(0, 0), (-1, 0), (1, 0), (-2, 0), (2, 0)];
+
+static ARS_KICKS: Kicks = &[(0, 0), (1, 0), (-1, 0)];
+static NO_KICKS: Kicks = &[(0, 0)];
+
+// the SRS tables are usually given with y pointing up, these are already flipped
+static SRS_JLSTZ_KICKS: [Kicks; 8] = [
+    &[(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],  // 0 -> R
+    &[(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],    // R -> 0
+    &[(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],    // R -> 2
+    &[(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],  // 2 -> R
+    &[(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],     // 2 -> L
+    &[(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)], // L -> 2
+    &[(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)], // L -> 0
+    &[(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],     // 0 -> L
+];
+
+static SRS_I_KICKS: [Kicks; 8] = [
+    &[(0, 0), (-2, 0), (1, 0), (-2, 1), (1, -2)],   // 0 -> R
+    &[(0, 0), (2, 0), (-1, 0), (2, -1), (-1, 2)],   // R -> 0
+    &[(0, 0), (-1, 0), (2, 0), (-1, -2), (2, 1)],   // R -> 2
+    &[(0, 0), (1, 0), (-2, 0), (1, 2), (-2, -1)],   // 2 -> R
+    &[(0, 0), (2, 0), (-1, 0), (2, -1), (-1, 2)],   // 2 -> L
+    &[(0, 0), (-2, 0), (1, 0), (-2, 1), (1, -2)],   // L -> 2
+    &[(0, 0), (1, 0), (-2, 0), (1, 2), (-2, -1)],   // L -> 0
+    &[(0, 0), (-1, 0), (2, 0), (-1, -2), (2, 1)],   // 0 -> L
+];
+
+// SRS has no official 180 kicks, this is the common "try a step up or sideways" set
+static SRS_180_KICKS: Kicks = &[(0, 0), (0, -1), (1, 0), (-1, 0)];
+
+fn srs_table_index(from: u8, to: u8) -> usize {
+    match (from, to) {
+        (0, 1) => 0,
+        (1, 0) => 1,
+        (1, 2) => 2,
+        (2, 1) => 3,
+        (2, 3) => 4,
+        (3, 2) => 5,
+        (3, 0) => 6,
+        _ => 7,
+    }
+}
+
+impl RotationSystem {
+    pub const ALL: [RotationSystem; 3] =
+        [RotationSystem::Classic, RotationSystem::Srs, RotationSystem::Ars];
+
+    // the offsets to try, in order, when rotating from one orientation to another
+    pub fn kicks(&self, piece_type: PieceType, from: u8, to: u8) -> Kicks {
+        match self {
+            RotationSystem::Classic => CLASSIC_KICKS,
+            RotationSystem::Ars => match piece_type {
+                PieceType::Straight | PieceType::Square => NO_KICKS,
+                _ => ARS_KICKS,
+            },
+            RotationSystem::Srs => {
+                if (from + 2) % 4 == to {
+                    return SRS_180_KICKS;
+                }
+                match piece_type {
+                    PieceType::Square => NO_KICKS,
+                    PieceType::Straight => SRS_I_KICKS[srs_table_index(from, to)],
+                    _ => SRS_JLSTZ_KICKS[srs_table_index(from, to)],
+                }
+            }
+        }
+    }
+}
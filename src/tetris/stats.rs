@@ -0,0 +1,95 @@
+use crate::piece::PieceType;
+use super::Verdict;
+
+// lines sent to an opponent for each number of cleared lines, guideline values
+const CLEAR_ATTACK: [usize; 5] = [0, 0, 1, 2, 4];
+const T_SPIN_ATTACK: [usize; 4] = [0, 2, 4, 6];
+// extra lines for consecutive clears, indexed by the combo counter
+const COMBO_ATTACK: [usize; 12] = [0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 4, 5];
+
+#[derive(Clone, Default)]
+pub struct Stats {
+    pub pieces: usize,
+    // inputs from the player, a held key counts once
+    pub keys: usize,
+    pub attack: usize,
+    // number of pieces in a row that cleared lines
+    pub combo: usize,
+    pub max_combo: usize,
+    // the last clear was a tetris or a T-spin, so the next one gets a bonus
+    pub back_to_back: bool,
+    pub piece_counts: [usize; 7],
+    // singles, doubles, triples and tetrises
+    pub clears: [usize; 4],
+    // T-spins clearing zero to three lines
+    pub t_spins: [usize; 4],
+    // pieces placed with more inputs than needed, and how many inputs that wasted
+    pub finesse_faults: usize,
+    pub wasted_inputs: usize,
+}
+
+impl Stats {
+    pub fn on_lock(&mut self, piece_type: PieceType, cleared: usize, t_spin: bool) {
+        self.pieces += 1;
+        self.piece_counts[piece_type.index()] += 1;
+
+        if t_spin {
+            self.t_spins[cleared.min(3)] += 1;
+        }
+        if cleared == 0 {
+            self.combo = 0;
+            return;
+        }
+
+        self.combo += 1;
+        self.max_combo = self.max_combo.max(self.combo);
+        if !t_spin {
+            self.clears[cleared.min(4) - 1] += 1;
+        }
+
+        let difficult = t_spin || cleared >= 4;
+        let mut attack = if t_spin {
+            T_SPIN_ATTACK[cleared.min(3)]
+        } else {
+            CLEAR_ATTACK[cleared.min(4)]
+        };
+        if difficult && self.back_to_back {
+            attack += 1;
+        }
+        attack += COMBO_ATTACK[self.combo.min(COMBO_ATTACK.len() - 1)];
+
+        self.back_to_back = difficult;
+        self.attack += attack;
+    }
+
+    pub fn on_finesse(&mut self, verdict: Option<Verdict>) {
+        if let Some(Verdict::Fault { used, needed }) = verdict {
+            self.finesse_faults += 1;
+            self.wasted_inputs += used - needed;
+        }
+    }
+
+    // pieces per second
+    pub fn pps(&self, elapsed_ms: u64) -> f64 {
+        if elapsed_ms == 0 {
+            return 0.0;
+        }
+        self.pieces as f64 * 1000.0 / elapsed_ms as f64
+    }
+
+    // attack per minute
+    pub fn apm(&self, elapsed_ms: u64) -> f64 {
+        if elapsed_ms == 0 {
+            return 0.0;
+        }
+        self.attack as f64 * 60_000.0 / elapsed_ms as f64
+    }
+
+    // keys per piece
+    pub fn kpp(&self) -> f64 {
+        if self.pieces == 0 {
+            return 0.0;
+        }
+        self.keys as f64 / self.pieces as f64
+    }
+}
@@ -1,31 +1,96 @@
-use std::time::Duration;
-use std::time;
-use crate::config;
+use std::time::{Duration, Instant};
+use crate::config::{GravityPreset, Rules};
+
+// how long the game has been going for
+pub enum Clock {
+    // paused_at is set while the game is paused, the clock doesn't move then
+    Wall { started: Instant, paused_at: Option<Instant> },
+    // driven by whoever owns the game, used for replays and simulations
+    Manual { now_ms: u64 },
+}
+
+impl Clock {
+    pub fn elapsed_ms(&self) -> u64 {
+        match self {
+            Clock::Wall { started, paused_at } =>
+                paused_at.unwrap_or_else(Instant::now).duration_since(*started).as_millis() as u64,
+            Clock::Manual { now_ms } => *now_ms,
+        }
+    }
+
+    pub fn set_ms(&mut self, ms: u64) {
+        *self = Clock::Manual { now_ms: ms };
+    }
+
+    // continue with real time from where the clock is now
+    pub fn switch_to_wall(&mut self) {
+        let elapsed = Duration::from_millis(self.elapsed_ms());
+        let now = Instant::now();
+        *self = Clock::Wall { started: now.checked_sub(elapsed).unwrap_or(now), paused_at: None };
+    }
+
+    fn pause(&mut self, now: Instant) {
+        if let Clock::Wall { paused_at, .. } = self {
+            paused_at.get_or_insert(now);
+        }
+    }
+
+    fn resume(&mut self, now: Instant) {
+        if let Clock::Wall { started, paused_at } = self {
+            if let Some(paused_at) = paused_at.take() {
+                *started += now.duration_since(paused_at);
+            }
+        }
+    }
+}
 
 pub struct TimeManager {
     pub tick_time: usize,
-    last: std::time::SystemTime,
+    pub clock: Clock,
+    last: Instant,
+    paused_at: Option<Instant>,
     sticky_timeout: usize,
     offset_tick: usize,
+    gravity: GravityPreset,
+    lock_delay: usize,
 }
 
 impl TimeManager {
+    // the current moment as far as the timers are concerned, frozen during a pause
+    fn now(&self) -> Instant {
+        self.paused_at.unwrap_or_else(Instant::now)
+    }
+
+    pub fn pause(&mut self) {
+        let now = Instant::now();
+        self.clock.pause(now);
+        self.paused_at.get_or_insert(now);
+    }
+
+    // shifts every timer by the length of the pause, as if it never happened
+    pub fn resume(&mut self) {
+        let now = Instant::now();
+        self.clock.resume(now);
+        if let Some(paused_at) = self.paused_at.take() {
+            self.last += now.duration_since(paused_at);
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
     pub fn tick(&mut self) {
-        self.last = time::SystemTime::now();
-        self.sticky_timeout = config::INITIAL_TICK_TIME_MS;
+        self.last = self.now();
+        self.sticky_timeout = self.lock_delay;
         self.offset_tick = 0;
     }
 
     pub fn get_timeout(&self) -> usize {
-        let elapsed = time::SystemTime::now().duration_since(self.last)
-            .unwrap_or(Duration::from_millis(0));
+        let elapsed = self.now().saturating_duration_since(self.last);
 
         let with_offset = self.offset_tick + elapsed.as_millis() as usize;
-        if self.tick_time >= with_offset {
-            self.tick_time - with_offset
-        } else {
-            0
-        }
+        self.tick_time.saturating_sub(with_offset)
     }
 
     pub fn should_finish_turn(&self) -> bool {
@@ -33,15 +98,13 @@ impl TimeManager {
     }
 
     pub fn update_tick_speed(&mut self, cleaned_up: usize) {
-        const QUICKENING_COEF: usize = 20;
-        const SPEED_CAP: usize = 20;
-        let tick_quickening = QUICKENING_COEF * cleaned_up;
+        let speed_cap = self.gravity.speed_cap_ms();
+        let tick_quickening = self.gravity.quickening_ms() * cleaned_up;
 
-        if self.tick_time >= tick_quickening + SPEED_CAP {
+        if self.tick_time >= tick_quickening + speed_cap {
             self.tick_time -= tick_quickening;
         } else {
-            // cap speed at 20ms per tick
-            self.tick_time = SPEED_CAP;
+            self.tick_time = speed_cap;
         }
     }
 
@@ -54,11 +117,14 @@ impl TimeManager {
             self.offset_tick = self.tick_time - self.sticky_timeout;
         }
 
-        self.last = time::SystemTime::now();
+        self.last = self.now();
     }
 
-    pub fn new() -> Self {
-        TimeManager {tick_time: config::INITIAL_TICK_TIME_MS, last: time::SystemTime::now(),
-                     sticky_timeout: config::INITIAL_TICK_TIME_MS, offset_tick: 0}
+    pub fn new(rules: &Rules) -> Self {
+        TimeManager {tick_time: rules.gravity.initial_tick_ms(),
+                     clock: Clock::Wall { started: Instant::now(), paused_at: None },
+                     last: Instant::now(), paused_at: None,
+                     sticky_timeout: rules.lock_delay_ms, offset_tick: 0,
+                     gravity: rules.gravity, lock_delay: rules.lock_delay_ms}
     }
 }
@@ -0,0 +1,230 @@
+// the colors the game is drawn in. Themes are picked in truecolor, each color with
+// the closest of the 16 basic terminal colors picked by hand, and brought down to
+// what the terminal can show.
+
+use std::env;
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+use tui::style::Color;
+
+use crate::config::{Theme, Visuals};
+use crate::piece::PieceColor;
+
+// how many colors the terminal can show
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum ColorDepth {
+    // worked out from COLORTERM and TERM
+    #[serde(rename = "auto")]
+    Auto,
+    #[serde(rename = "truecolor")]
+    TrueColor,
+    #[serde(rename = "256")]
+    Indexed,
+    #[serde(rename = "16")]
+    Basic,
+}
+
+impl ColorDepth {
+    pub const ALL: [ColorDepth; 4] =
+        [ColorDepth::Auto, ColorDepth::TrueColor, ColorDepth::Indexed, ColorDepth::Basic];
+
+    // what the terminal the game runs in says it can show, looked up once
+    pub fn detect() -> ColorDepth {
+        static DETECTED: OnceLock<ColorDepth> = OnceLock::new();
+        *DETECTED.get_or_init(|| ColorDepth::from_env(env::var("COLORTERM").ok().as_deref(),
+                                                      env::var("TERM").ok().as_deref()))
+    }
+
+    pub fn from_env(colorterm: Option<&str>, term: Option<&str>) -> ColorDepth {
+        match (colorterm, term) {
+            (Some("truecolor" | "24bit"), _) => ColorDepth::TrueColor,
+            (_, Some(term)) if term.contains("256color") => ColorDepth::Indexed,
+            _ => ColorDepth::Basic,
+        }
+    }
+
+    pub fn resolve(self) -> ColorDepth {
+        match self {
+            ColorDepth::Auto => ColorDepth::detect(),
+            depth => depth,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Shade {
+    // one of the terminal's own colors, the same at every depth
+    Basic(Color),
+    // a truecolor one and the basic color that stands in for it
+    Rgb(u8, u8, u8, Color),
+}
+
+impl Shade {
+    pub fn at(self, depth: ColorDepth) -> Color {
+        match (self, depth.resolve()) {
+            (Shade::Basic(color), _) | (Shade::Rgb(.., color), ColorDepth::Basic) => color,
+            (Shade::Rgb(r, g, b, _), ColorDepth::Indexed) => Color::Indexed(to_256(r, g, b)),
+            (Shade::Rgb(r, g, b, _), _) => Color::Rgb(r, g, b),
+        }
+    }
+}
+
+// the nearest color of the xterm 256 color cube or its grey ramp
+fn to_256(r: u8, g: u8, b: u8) -> u8 {
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    let distance = |(r2, g2, b2): (u8, u8, u8)| {
+        [(r, r2), (g, g2), (b, b2)].iter()
+            .map(|&(a, b)| (a as i32 - b as i32).pow(2)).sum::<i32>()
+    };
+    let nearest = |value: u8| (0..6)
+        .min_by_key(|&idx| (LEVELS[idx] as i32 - value as i32).abs()).unwrap_or(0);
+
+    let (ri, gi, bi) = (nearest(r), nearest(g), nearest(b));
+    let cube = (LEVELS[ri], LEVELS[gi], LEVELS[bi]);
+    // the ramp goes from 8 to 238 in steps of 10
+    let step = ((r as u32 + g as u32 + b as u32) / 3).saturating_sub(3) / 10;
+    let step = step.min(23) as u8;
+    let grey = 8 + 10 * step;
+
+    if distance((grey, grey, grey)) < distance(cube) {
+        232 + step
+    } else {
+        16 + 36 * ri as u8 + 6 * gi as u8 + bi as u8
+    }
+}
+
+// the colors of the pieces, in the order of PieceType::ALL, and of what else is drawn
+// on the board
+pub struct Palette {
+    pub pieces: [Shade; 7],
+    pub ghost: Shade,
+    pub target: Shade,
+    // the rows the ghost would complete
+    pub clearing: Shade,
+    // what the animations light up
+    pub flash: Shade,
+}
+
+pub fn palette(theme: Theme) -> Palette {
+    use tui::style::Color::*;
+    let clearing = Shade::Rgb(200, 200, 200, White);
+    let flash = Shade::Rgb(255, 255, 255, White);
+
+    match theme {
+        Theme::Classic => Palette {
+            pieces: [Red, Green, LightBlue, Blue, LightYellow, Yellow, Magenta].map(Shade::Basic),
+            ghost: Shade::Basic(Gray),
+            target: Shade::Basic(White),
+            clearing,
+            flash,
+        },
+        Theme::Guideline => Palette {
+            pieces: [
+                Shade::Rgb(240, 240, 0, LightYellow),
+                Shade::Rgb(240, 160, 0, Yellow),
+                Shade::Rgb(0, 240, 240, LightCyan),
+                Shade::Rgb(0, 0, 240, Blue),
+                Shade::Rgb(160, 0, 240, Magenta),
+                Shade::Rgb(240, 0, 0, Red),
+                Shade::Rgb(0, 240, 0, Green),
+            ],
+            ghost: Shade::Basic(Gray),
+            target: Shade::Basic(White),
+            clearing,
+            flash,
+        },
+        Theme::Monochrome => Palette {
+            pieces: [Shade::Basic(White); 7],
+            ghost: Shade::Basic(Gray),
+            target: Shade::Basic(White),
+            clearing: Shade::Rgb(200, 200, 200, Gray),
+            // the pieces are white already
+            flash: Shade::Basic(Gray),
+        },
+        // bright pieces on a dark ghost, told apart by lightness as much as by hue
+        Theme::HighContrast => Palette {
+            pieces: [
+                Shade::Rgb(255, 255, 0, LightYellow),
+                Shade::Rgb(255, 135, 0, Yellow),
+                Shade::Rgb(0, 255, 255, LightCyan),
+                Shade::Rgb(80, 120, 255, LightBlue),
+                Shade::Rgb(255, 0, 255, LightMagenta),
+                Shade::Rgb(255, 40, 40, LightRed),
+                Shade::Rgb(0, 255, 0, LightGreen),
+            ],
+            ghost: Shade::Basic(DarkGray),
+            target: Shade::Rgb(170, 170, 170, Gray),
+            clearing: Shade::Basic(White),
+            flash,
+        },
+        // the Okabe-Ito palette, which stays apart with deuteranopia and protanopia. The
+        // basic colors can't do that, patterns help there.
+        Theme::Colorblind => Palette {
+            pieces: [
+                Shade::Rgb(240, 228, 66, LightYellow),
+                Shade::Rgb(230, 159, 0, Yellow),
+                Shade::Rgb(86, 180, 233, LightCyan),
+                Shade::Rgb(0, 114, 178, Blue),
+                Shade::Rgb(204, 121, 167, Magenta),
+                Shade::Rgb(213, 94, 0, Red),
+                Shade::Rgb(0, 158, 115, Green),
+            ],
+            ghost: Shade::Basic(Gray),
+            target: Shade::Basic(White),
+            clearing,
+            flash,
+        },
+    }
+}
+
+fn index(color: PieceColor) -> usize {
+    match color {
+        PieceColor::Red => 0,
+        PieceColor::Green => 1,
+        PieceColor::LightBlue => 2,
+        PieceColor::Blue => 3,
+        PieceColor::LightYellow => 4,
+        PieceColor::Yellow => 5,
+        PieceColor::Magenta => 6,
+    }
+}
+
+// two characters per piece that tell the pieces apart without color
+pub fn pattern(color: PieceColor) -> &'static str {
+    ["[]", "<<", "==", ">>", "##", "%%", "~~"][index(color)]
+}
+
+// a palette brought down to the colors the terminal shows, ready to draw with
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Colors {
+    pub pieces: [Color; 7],
+    pub ghost: Color,
+    pub target: Color,
+    pub clearing: Color,
+    pub flash: Color,
+    pub patterns: bool,
+}
+
+impl Colors {
+    pub fn new(visuals: &Visuals) -> Self {
+        let palette = palette(visuals.theme);
+        let depth = visuals.color_depth.resolve();
+        Colors {
+            pieces: palette.pieces.map(|shade| shade.at(depth)),
+            ghost: palette.ghost.at(depth),
+            target: palette.target.at(depth),
+            clearing: palette.clearing.at(depth),
+            flash: palette.flash.at(depth),
+            patterns: visuals.patterns,
+        }
+    }
+
+    pub fn piece(&self, color: PieceColor) -> Color {
+        self.pieces[index(color)]
+    }
+
+    pub fn pattern(&self, color: PieceColor) -> Option<&'static str> {
+        self.patterns.then(|| pattern(color))
+    }
+}
@@ -0,0 +1,480 @@
+use tui::{
+    backend::Backend,
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Widget},
+    text::{Span, Spans},
+    Frame, Terminal,
+};
+
+use crate::animation::Overlay;
+use crate::config::{self, Renderer, Visuals};
+use crate::piece::{self, Piece, PieceType};
+use crate::tetris::{Delay, Outcome, Tetris, Verdict};
+use crate::theme::Colors;
+
+// widgets panic when drawn outside of the terminal, so anything that doesn't fit is skipped
+fn fits(rect: &Rect, area: Rect) -> bool {
+    rect.right() <= area.right() && rect.bottom() <= area.bottom()
+}
+
+// the part of rect inside area, empty when they don't meet
+fn clip(rect: Rect, area: Rect) -> Rect {
+    if rect.intersects(area) {
+        rect.intersection(area)
+    } else {
+        Rect { width: 0, height: 0, ..area }
+    }
+}
+
+pub fn put_message_on_screen<B: Backend>(message: &str, terminal: &mut Terminal<B>) {
+
+    terminal.draw(|frame| {
+        let screen = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(5)
+            .constraints([Constraint::Ratio(1, 3),
+                          Constraint::Ratio(1, 3),
+                          Constraint::Ratio(1, 3)].as_ref())
+            .split(frame.size());
+
+        let screen = Layout::default()
+            .direction(Direction::Horizontal)
+            .margin(5)
+            .constraints([Constraint::Ratio(1, 3),
+                          Constraint::Ratio(1, 3),
+                          Constraint::Ratio(1, 3)].as_ref())
+            .split(screen[1]);
+
+        let paragraph = Paragraph::new(message)
+            .block(Block::default().borders(Borders::ALL))
+            .alignment(Alignment::Center);
+        frame.render_widget(paragraph, screen[1]);
+    }).expect("Failed to display image on screen");
+}
+
+// how many rows the next panel needs, every previewed piece takes up two block rows
+// and a spacer
+fn next_height(game: &Tetris) -> u16 {
+    (game.next_pieces.len() * (2 * config::BLOCK_HEIGHT + 1) + 2) as u16
+}
+
+// the columns and lines a grid of cells takes up on the terminal
+fn grid_size(renderer: Renderer, cols: usize, rows: usize) -> (u16, u16) {
+    let (width, height) = match renderer {
+        Renderer::Blocks => (cols * config::BLOCK_WIDTH, rows * config::BLOCK_HEIGHT),
+        Renderer::Compact => (cols * 2, rows),
+        Renderer::HalfBlock => (cols, rows.div_ceil(2)),
+    };
+    (width as u16, height as u16)
+}
+
+pub fn draw_game<B: Backend>(f: &mut Frame<B>, game: &Tetris, visuals: &Visuals) {
+    draw_animated_game(f, game, visuals, &Overlay::default());
+}
+
+// the game with what the animations put over the board
+pub fn draw_animated_game<B: Backend>(f: &mut Frame<B>, game: &Tetris, visuals: &Visuals,
+                                      overlay: &Overlay) {
+    if visuals.renderer != Renderer::Blocks {
+        return draw_compact_game(f, game, visuals, overlay);
+    }
+    let screen = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(
+                (game.width() * config::BLOCK_WIDTH) as u16),
+                      Constraint::Length(30),
+                      Constraint::Min(0)].as_ref())
+        .split(f.size());
+
+    let other = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(next_height(game)),
+                      Constraint::Length((2 * config::BLOCK_HEIGHT + 3) as u16),
+                      Constraint::Min(0)].as_ref())
+        .split(screen[1]);
+
+    f.render_widget(BoardWidget { game, visuals, overlay }, screen[0]);
+    draw_next(f, other[0], game, visuals);
+    draw_spare(f, other[1], game, visuals);
+    draw_score(f, other[2], game);
+}
+
+// the board with the pieces in a narrow column next to it and the score beside them
+fn draw_compact_game<B: Backend>(f: &mut Frame<B>, game: &Tetris, visuals: &Visuals,
+                                 overlay: &Overlay) {
+    let (board_width, board_height) = grid_size(visuals.renderer, game.width(), game.height());
+    let (piece_width, piece_height) = grid_size(visuals.renderer, 4, 2);
+    let screen = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(board_width + 2),
+                      Constraint::Length(piece_width + 4),
+                      Constraint::Length(30),
+                      Constraint::Min(0)].as_ref())
+        .split(f.size());
+
+    let pieces = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(game.next_pieces.len() as u16 * (piece_height + 1) + 1),
+                      Constraint::Length(piece_height + 2),
+                      Constraint::Min(0)].as_ref())
+        .split(screen[1]);
+
+    // a board taller than the terminal is cut off at the bottom
+    let board = clip(Rect { height: board_height + 2, ..screen[0] }, f.size());
+    f.render_widget(BoardWidget { game, visuals, overlay }, board);
+    draw_next(f, pieces[0], game, visuals);
+    draw_spare(f, pieces[1], game, visuals);
+    draw_score(f, Rect { height: screen[2].height.min(20), ..screen[2] }, game);
+}
+
+// writes a rounded border, and the background color over the whole rect if there is
+// one, as the Block widget would. Without a background only the edges are touched,
+// where the widget goes over every cell inside too. What is outside the buffer is left out.
+fn paint_block(buf: &mut Buffer, rect: Rect, bg: Option<Color>) {
+    let rect = clip(rect, buf.area);
+    if let Some(bg) = bg {
+        buf.set_style(rect, Style::default().bg(bg));
+    }
+    if rect.width < 2 || rect.height < 2 {
+        return;
+    }
+    let symbols = BorderType::line_symbols(BorderType::Rounded);
+    let (left, right) = (rect.left(), rect.right() - 1);
+    let (top, bottom) = (rect.top(), rect.bottom() - 1);
+    for x in left..=right {
+        let (upper, lower) = match x {
+            x if x == left => (symbols.top_left, symbols.bottom_left),
+            x if x == right => (symbols.top_right, symbols.bottom_right),
+            _ => (symbols.horizontal, symbols.horizontal),
+        };
+        buf.get_mut(x, top).set_symbol(upper);
+        buf.get_mut(x, bottom).set_symbol(lower);
+    }
+    for y in top + 1..bottom {
+        buf.get_mut(left, y).set_symbol(symbols.vertical);
+        buf.get_mut(right, y).set_symbol(symbols.vertical);
+    }
+}
+
+// the color of a cell, and the pattern of two characters it is drawn with if any
+type Fill = (Color, Option<&'static str>);
+
+// the k-th character of a pattern, by characters rather than bytes so a pattern can have
+// any symbols. It repeats when it is shorter.
+pub fn pattern_symbol(pattern: &str, k: usize) -> &str {
+    let (start, symbol) = pattern.char_indices().cycle().nth(k).expect("patterns aren't empty");
+    &pattern[start..start + symbol.len_utf8()]
+}
+
+// writes a grid of cells straight into the buffer the way the renderer draws them,
+// `fill` gives what is in the cell in a row and column, if there is anything there.
+// There is no room for the patterns in half blocks.
+fn paint_cells(buf: &mut Buffer, area: Rect, renderer: Renderer, (rows, cols): (usize, usize),
+               fill: impl Fn(usize, usize) -> Option<Fill>) {
+    let color = |i, j| fill(i, j).map(|(color, _)| color);
+    let mut put = |x: usize, y: usize, symbol: &str, style: Style| {
+        let (x, y) = (area.x + x as u16, area.y + y as u16);
+        if x < area.right() && y < area.bottom() {
+            buf.get_mut(x, y).set_symbol(symbol).set_style(style);
+        }
+    };
+
+    match renderer {
+        Renderer::Blocks => for i in 0..rows {
+            for (j, (color, pattern)) in (0..cols).filter_map(|j| fill(i, j).map(|f| (j, f))) {
+                let rect = Rect {
+                    x: area.x + (j * config::BLOCK_WIDTH) as u16,
+                    y: area.y + (i * config::BLOCK_HEIGHT) as u16,
+                    width: config::BLOCK_WIDTH as u16, height: config::BLOCK_HEIGHT as u16};
+                if !fits(&rect, area) {
+                    continue;
+                }
+                paint_block(buf, rect, Some(color));
+                // in place of the top and bottom edges
+                if let Some(pattern) = pattern {
+                    for y in [rect.top(), rect.bottom() - 1] {
+                        for (k, x) in (rect.left() + 1..rect.right() - 1).enumerate() {
+                            buf.get_mut(x, y).set_symbol(pattern_symbol(pattern, k))
+                                .set_fg(Color::Black);
+                        }
+                    }
+                }
+            }
+        },
+        Renderer::Compact => for i in 0..rows {
+            for (j, (color, pattern)) in (0..cols).filter_map(|j| fill(i, j).map(|f| (j, f))) {
+                let (left, right) = pattern.map_or(("█", "█"), |pattern| {
+                    (pattern_symbol(pattern, 0), pattern_symbol(pattern, 1))
+                });
+                put(2 * j, i, left, Style::default().fg(color));
+                put(2 * j + 1, i, right, Style::default().fg(color));
+            }
+        },
+        Renderer::HalfBlock => for line in 0..rows.div_ceil(2) {
+            for j in 0..cols {
+                let bottom = if 2 * line + 1 < rows { color(2 * line + 1, j) } else { None };
+                match (color(2 * line, j), bottom) {
+                    (None, None) => (),
+                    (Some(top), None) => put(j, line, "▀", Style::default().fg(top)),
+                    (None, Some(bottom)) => put(j, line, "▄", Style::default().fg(bottom)),
+                    (Some(top), Some(bottom)) =>
+                        put(j, line, "▀", Style::default().fg(top).bg(bottom)),
+                }
+            }
+        },
+    }
+}
+
+// the board and its border in one pass over the cells: the pile, the target of the
+// trainer, the ghost and the falling piece. Rows that the ghost would complete are
+// greyed out. The animations go on top.
+pub struct BoardWidget<'a> {
+    pub game: &'a Tetris,
+    pub visuals: &'a Visuals,
+    pub overlay: &'a Overlay,
+}
+
+impl Widget for BoardWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let area = clip(area, buf.area);
+        let (game, colors, overlay) = (self.game, Colors::new(self.visuals), self.overlay);
+        paint_block(buf, area, None);
+        if overlay.flash && area.width >= 2 && area.height >= 2 {
+            let style = Style::default().fg(colors.flash).add_modifier(Modifier::BOLD);
+            for x in area.left()..area.right() {
+                buf.get_mut(x, area.top()).set_style(style);
+                buf.get_mut(x, area.bottom() - 1).set_style(style);
+            }
+            for y in area.top()..area.bottom() {
+                buf.get_mut(area.left(), y).set_style(style);
+                buf.get_mut(area.right() - 1, y).set_style(style);
+            }
+        }
+        if area.width > 2 && area.height > 0 {
+            buf.set_stringn(area.x + 1, area.y, "Tetris", area.width as usize - 2,
+                            Style::default());
+        }
+        // the blocks are drawn over the border, the other renderers stay inside it
+        let cells = match self.visuals.renderer {
+            Renderer::Blocks => area,
+            _ => Block::default().borders(Borders::ALL).inner(area),
+        };
+
+        // between two pieces there is only the pile, with the lines that are clearing
+        let waiting = game.delay.is_some();
+        let potentionally_completed_lines = match &game.delay {
+            Some(Delay::LineClear { rows, .. }) => rows.clone(),
+            Some(Delay::Entry { .. }) => Vec::new(),
+            None => game.pile.get_complete_lines_with(&game.projected_piece.get_positions()),
+        };
+        let current = game.current_piece.get_positions();
+        let current_color = piece::get_piece_color(&game.current_piece.piece_type);
+        let ghost = game.projected_piece.get_positions();
+        let target = game.target.as_ref().map(|target| target.piece.get_positions());
+        let width = game.width();
+
+        paint_cells(buf, cells, self.visuals.renderer, (game.height(), width), |i, j| {
+            // a wiped row is lit up from its middle to as far as the wipe has not got yet
+            let from_middle = (2 * j + 1).abs_diff(width) as f32 / width as f32;
+            if overlay.wipes(i).is_some_and(|progress| from_middle >= progress) {
+                return Some((colors.flash, None));
+            }
+            if let Some(piece_color) = game.pile.colors[i * width + j] {
+                let color = if overlay.locked.contains(&(i, j)) {
+                    colors.flash
+                } else if potentionally_completed_lines.contains(&i) {
+                    colors.clearing
+                } else {
+                    colors.piece(piece_color)
+                };
+                Some((color, colors.pattern(piece_color)))
+            } else if waiting {
+                None
+            } else if current.contains(&(i, j)) {
+                Some((colors.piece(current_color), colors.pattern(current_color)))
+            } else if ghost.contains(&(i, j)) {
+                Some((colors.ghost, None))
+            } else if target.is_some_and(|target| target.contains(&(i, j))) {
+                Some((colors.target, None))
+            } else {
+                None
+            }
+        });
+
+        let style = Style::default().fg(colors.flash).bg(Color::Reset)
+            .add_modifier(Modifier::BOLD);
+        for (text, row) in overlay.callouts.iter() {
+            let line = match self.visuals.renderer {
+                Renderer::Blocks => row * config::BLOCK_HEIGHT,
+                Renderer::Compact => *row,
+                Renderer::HalfBlock => row / 2,
+            } as u16;
+            // cut short on a narrow board
+            let len = (text.chars().count() as u16 + 2).min(cells.width);
+            if line >= cells.height {
+                continue;
+            }
+            let x = cells.x + (cells.width - len) / 2;
+            buf.set_stringn(x, cells.y + line, format!(" {} ", text), len as usize, style);
+        }
+    }
+}
+
+pub fn draw_board<B: Backend>(f: &mut Frame<B>, area: Rect, game: &Tetris, visuals: &Visuals) {
+    f.render_widget(BoardWidget { game, visuals, overlay: &Overlay::default() }, area);
+}
+
+// a preview piece, drawn relative to the top left corner of its panel
+struct PieceWidget<'a> {
+    piece: &'a Piece,
+    visuals: &'a Visuals,
+}
+
+impl Widget for PieceWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let inner = Rect {
+            x: area.x + 2,
+            y: area.y + 1,
+            width: area.width.saturating_sub(2),
+            height: area.height.saturating_sub(1),
+        };
+        let positions = self.piece.get_positions();
+        let colors = Colors::new(self.visuals);
+        let piece_color = piece::get_piece_color(&self.piece.piece_type);
+        let fill = (colors.piece(piece_color), colors.pattern(piece_color));
+        paint_cells(buf, inner, self.visuals.renderer, (2, 4),
+                    |i, j| positions.contains(&(i, j)).then_some(fill));
+    }
+}
+
+pub fn draw_next<B: Backend>(f: &mut Frame<B>, area: Rect, game: &Tetris, visuals: &Visuals) {
+    f.render_widget(Block::default().title("Next").borders(Borders::ALL), area);
+
+    let spacing = match visuals.renderer {
+        Renderer::Blocks => (2 * config::BLOCK_HEIGHT + 1) as u16,
+        renderer => grid_size(renderer, 4, 2).1 + 1,
+    };
+    for (idx, piece) in game.next_pieces.iter().enumerate() {
+        let offset = idx as u16 * spacing;
+        let area = Rect {
+            y: area.y + offset,
+            height: area.height.saturating_sub(offset),
+            ..area
+        };
+        f.render_widget(PieceWidget { piece, visuals }, area);
+    }
+}
+
+pub fn draw_spare<B: Backend>(f: &mut Frame<B>, area: Rect, game: &Tetris, visuals: &Visuals) {
+    f.render_widget(Block::default().title("Spare").borders(Borders::ALL), area);
+    f.render_widget(PieceWidget { piece: &game.spare_piece, visuals }, area);
+}
+
+pub fn draw_score<B: Backend>(f: &mut Frame<B>, area: Rect, game: &Tetris) {
+    f.render_widget(Block::default().title("Score").borders(Borders::ALL), area);
+
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    let mut lines = vec![
+        Spans::from(Span::styled(format!("Score: {}", game.score), bold)),
+        Spans::from(Span::styled(format!("Last combo: {}", game.last_combo), bold)),
+        Spans::from(Span::styled(format!("Tick speed: {}", game.get_tick_speed()), bold)),
+    ];
+    if let Some(target) = &game.target {
+        lines.push(Spans::from(Span::styled(
+            format!("Target: {} inputs", target.inputs), bold)));
+    }
+    lines.push(Spans::from(Span::styled(verdict_line(game.last_verdict), bold)));
+    lines.push(Spans::from(""));
+    lines.extend(stats_lines(game).into_iter().map(Spans::from));
+    f.render_widget(Paragraph::new(lines), Rect {
+        x: area.x + 2,
+        y: area.y + 2,
+        height: area.height.saturating_sub(3),
+        width: area.width.saturating_sub(3),
+    });
+}
+
+// the detailed statistics of a game, shown next to the board and on the game over screen
+pub fn stats_lines(game: &Tetris) -> Vec<String> {
+    let stats = &game.stats;
+    let elapsed = game.elapsed_ms();
+    let counts = |types: &[PieceType]| types.iter()
+        .map(|t| format!("{} {}", t.letter(), stats.piece_counts[t.index()]))
+        .collect::<Vec<_>>().join("  ");
+
+    vec![
+        format!("Lines: {}  Time: {}", game.lines, format_time(elapsed)),
+        format!("Pieces: {}  Max combo: {}", stats.pieces, stats.max_combo),
+        format!("PPS: {:.2}  APM: {:.1}", stats.pps(elapsed), stats.apm(elapsed)),
+        format!("KPP: {:.2}  Attack: {}", stats.kpp(), stats.attack),
+        format!("Clears: {} / {} / {} / {}",
+                stats.clears[0], stats.clears[1], stats.clears[2], stats.clears[3]),
+        format!("T-spins: {} / {} / {} / {}",
+                stats.t_spins[0], stats.t_spins[1], stats.t_spins[2], stats.t_spins[3]),
+        format!("Finesse faults: {} (+{})", stats.finesse_faults, stats.wasted_inputs),
+        counts(&PieceType::ALL[..4]),
+        counts(&PieceType::ALL[4..]),
+    ]
+}
+
+// a centered box with a list of lines, the selected one highlighted
+pub fn draw_menu<B: Backend>(f: &mut Frame<B>, title: &str, items: &[String],
+                             selected: Option<usize>, footer: &[String]) {
+    let height = (items.len() + footer.len() + 4) as u16;
+    let width = items.iter().chain(footer.iter())
+        .map(|line| line.chars().count())
+        .max().unwrap_or(0).max(title.len()) as u16 + 8;
+
+    let size = f.size();
+    let area = Rect {
+        x: size.width.saturating_sub(width) / 2,
+        y: size.height.saturating_sub(height) / 2,
+        width: width.min(size.width),
+        height: height.min(size.height),
+    };
+
+    let mut lines: Vec<Spans> = items.iter().enumerate().map(|(idx, item)| {
+        let style = if Some(idx) == selected {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default()
+        };
+        Spans::from(Span::styled(item.clone(), style))
+    }).collect();
+    if !footer.is_empty() {
+        lines.push(Spans::from(""));
+        lines.extend(footer.iter().map(|line| Spans::from(line.clone())));
+    }
+
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().title(title).borders(Borders::ALL).border_type(BorderType::Rounded))
+        .alignment(Alignment::Center);
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+fn verdict_line(verdict: Option<Verdict>) -> String {
+    match verdict {
+        Some(Verdict::Clean) => "Finesse: clean".to_string(),
+        Some(Verdict::Fault { used, needed }) =>
+            format!("Finesse: {} inputs, {} needed", used, needed),
+        None => "Finesse: -".to_string(),
+    }
+}
+
+pub fn format_time(ms: u64) -> String {
+    format!("{}:{:02}.{}", ms / 60_000, ms / 1000 % 60, ms / 100 % 10)
+}
+
+pub fn summary(game: &Tetris) -> String {
+    let result = match game.outcome() {
+        Some(Outcome::Completed) => "completed",
+        Some(Outcome::ToppedOut) => "topped out",
+        None => "unfinished",
+    };
+    format!("{} ({}), seed {}: score {}, {} lines in {}",
+            game.mode, result, game.seed, game.score, game.lines,
+            format_time(game.elapsed_ms()))
+}
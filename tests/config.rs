@@ -0,0 +1,24 @@
+// what a config file may bind keys to
+
+use std::path::Path;
+
+use tui_tetris::config::Config;
+use tui_tetris::controls::Action;
+use tui_tetris::input::Key;
+
+fn load(contents: &str) -> Result<Config, String> {
+    Config::from_toml(Path::new("config.toml"), contents).map_err(|err| err.to_string())
+}
+
+#[test]
+fn a_key_listed_twice_for_one_action_is_bound_once() {
+    let config = load("[keys]\nhold = [\"c\", \"s\", \"c\"]\n").unwrap();
+    assert_eq!(config.keys.keys_for(Action::Hold), [Key::Char('c'), Key::Char('s')]);
+    assert_eq!(config.keys.action_for(Key::Char('c')), Some(Action::Hold));
+}
+
+#[test]
+fn a_key_for_two_actions_is_a_conflict() {
+    let err = load("[keys]\nhold = \"c\"\npause = [\"p\", \"c\"]\n").unwrap_err();
+    assert!(err.contains("`c` is already bound to `keys."), "{}", err);
+}
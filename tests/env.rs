@@ -0,0 +1,118 @@
+// the step/reset environment agents are trained on
+
+use tui_tetris::bot::Bot;
+use tui_tetris::config::Rules;
+use tui_tetris::env::{Action, Env, InvalidAction, Rewards, Step};
+use tui_tetris::tetris::{Input, Mode, Tetris, Verdict};
+
+fn env(rewards: Rewards) -> Env {
+    Env::new(Rules { width: 10, height: 12, ..Rules::default() }, Mode::Marathon, rewards)
+}
+
+// the first placement of every piece, or a hard drop where it is when there is none
+fn first_placement(env: &mut Env) -> Action {
+    if env.placements().is_empty() { Action::Input(Input::HardDrop) } else { Action::Place(0) }
+}
+
+#[test]
+fn same_seed_and_actions_same_game() {
+    let (mut one, mut other) = (env(Rewards::default()), env(Rewards::default()));
+    assert_eq!(one.reset(42), other.reset(42));
+    for turn in 0..40 {
+        let action = match turn % 4 {
+            0 => Action::Input(Input::MoveLeft),
+            1 => Action::Input(Input::RotateCw),
+            _ => first_placement(&mut one),
+        };
+        let (one_step, other_step) = (one.step(action).unwrap(), other.step(action).unwrap());
+        assert_eq!(one_step.observation, other_step.observation);
+        assert_eq!((one_step.reward, one_step.done), (other_step.reward, other_step.done));
+    }
+
+    // another seed is another game
+    assert_ne!(one.reset(42).queue, other.reset(43).queue);
+}
+
+#[test]
+fn reset_starts_over() {
+    let mut env = env(Rewards::default());
+    let start = env.reset(7);
+    for _ in 0..10 {
+        let action = first_placement(&mut env);
+        env.step(action).unwrap();
+    }
+    assert!(env.game().stats.pieces > 0);
+    assert_ne!(env.observation(), start);
+
+    assert_eq!(env.reset(7), start);
+    let game = env.game();
+    assert_eq!((game.score, game.lines, game.stats.pieces, game.elapsed_ms()), (0, 0, 0, 0));
+}
+
+#[test]
+fn rewards_follow_the_weights() {
+    let rewards = Rewards { lines: 3.0, score: 0.5, survival: 0.25, top_out: -7.0 };
+    let mut env = env(rewards);
+    env.reset(1);
+    let bot = Bot::default();
+    let mut total_lines = 0;
+    for _ in 0..30 {
+        let plan = bot.plan(env.game()).expect("the bot keeps up on an open board");
+        for input in plan.inputs {
+            let (lines, score) = (env.game().lines, env.game().score);
+            let step = env.step(Action::Input(input)).unwrap();
+            let (lines, score) = (env.game().lines - lines, env.game().score - score);
+            total_lines += lines;
+            assert!(!step.done);
+            assert_eq!(step.reward, 3.0 * lines as f64 + 0.5 * score as f64 + 0.25);
+        }
+    }
+    assert!(total_lines > 0);
+}
+
+#[test]
+fn done_once_topped_out_and_nothing_after() {
+    let rewards = Rewards { lines: 1.0, score: 0.0, survival: 1.0, top_out: -10.0 };
+    let mut env = env(rewards);
+    env.reset(3);
+    let last = loop {
+        let step = env.step(Action::Input(Input::HardDrop)).unwrap();
+        if step.done {
+            break step;
+        }
+        assert_eq!(step.reward, 1.0);
+    };
+    assert_eq!(last.reward, -10.0);
+
+    // the game stays over and pays nothing
+    let Step { observation, reward, done } = env.step(Action::Input(Input::MoveLeft)).unwrap();
+    assert_eq!((observation, reward, done), (last.observation, 0.0, true));
+}
+
+#[test]
+fn placements_out_of_range() {
+    let mut env = env(Rewards::default());
+    env.reset(0);
+    let count = env.placements().len();
+    assert!(count > 0);
+    assert_eq!(env.step(Action::Place(count)).unwrap_err(), InvalidAction(count));
+    // nothing was played
+    assert_eq!(env.game().stats.keys, 0);
+}
+
+#[test]
+fn finesse_is_not_judged() {
+    let mut env = env(Rewards::default());
+    env.reset(0);
+    // a move back and forth is a finesse fault in a game someone plays
+    let inputs = [Input::MoveLeft, Input::MoveRight, Input::HardDrop];
+    let mut played = Tetris::new(&env.rules, Mode::Marathon, 0);
+    for input in inputs {
+        played.apply(input);
+        env.step(Action::Input(input)).unwrap();
+    }
+    assert!(matches!(played.last_verdict, Some(Verdict::Fault { .. })));
+    assert_eq!(env.game().stats.pieces, 1);
+    assert_eq!(env.game().last_verdict, None);
+    assert_eq!(env.game().stats.finesse_faults, 0);
+}
@@ -0,0 +1,133 @@
+// placements found by the move generator, and the inputs it gives to get there
+
+use std::collections::HashSet;
+
+use tui_tetris::config::Rules;
+use tui_tetris::piece::{self, Piece, PieceType};
+use tui_tetris::tetris::movegen::{self, Move};
+use tui_tetris::tetris::{finesse, GameEvent, Input, Mode, RotationSystem, Tetris};
+
+// a 10x8 board with the given rows at the bottom, '#' is taken. Cleared lines stay on the
+// board for a while, so a lock always shows where the piece went.
+fn board(rows: &[&str]) -> Tetris {
+    let rules = Rules { width: 10, height: 8, rotation: RotationSystem::Srs,
+                        line_clear_delay_ms: 100, ..Rules::default() };
+    let mut game = Tetris::new(&rules, Mode::Marathon, 1);
+    game.set_elapsed_ms(0);
+    let (width, top) = (game.width(), game.height() - rows.len());
+    let color = piece::get_piece_color(&PieceType::Square);
+    for (i, row) in rows.iter().enumerate() {
+        for (j, cell) in row.chars().enumerate() {
+            if cell == '#' {
+                game.pile.rows[top + i] |= 1 << j;
+                game.pile.colors[(top + i) * width + j] = Some(color);
+            }
+        }
+    }
+    game
+}
+
+fn spawned(game: &mut Tetris, piece_type: PieceType) -> Piece {
+    game.current_piece = Piece::new(piece_type, game.width() as i16 / 2 - 2, 0);
+    game.current_piece.clone()
+}
+
+fn cells(mut cells: Vec<(usize, usize)>) -> finesse::Placement {
+    cells.sort_unstable();
+    cells.try_into().expect("four cells")
+}
+
+// plays the inputs of a move on a new board and checks the piece locks where the move
+// said, with a T-spin called out when the move was one
+fn replay(rows: &[&str], piece: &Piece, found: &Move) {
+    let mut game = board(rows);
+    game.current_piece = piece.clone();
+    let taken = game.pile.cells().count();
+    for input in found.inputs.iter() {
+        assert_eq!(game.stats.pieces, 0, "locked before {:?} of {:?}", input, found.inputs);
+        game.apply(*input);
+    }
+    assert_eq!(game.pile.cells().count(), taken + 4);
+    assert!(finesse::placement(&found.piece).iter().all(|&cell| game.pile.contains(cell)));
+    let events: Vec<GameEvent> = game.take_events().collect();
+    let t_spin = events.iter()
+        .any(|event| matches!(event, GameEvent::Cleared { t_spin: true, .. }));
+    assert_eq!(t_spin, found.t_spin, "{:?}", found.inputs);
+}
+
+#[test]
+fn every_placement_on_an_empty_board_once() {
+    let rows = [];
+    let mut game = board(&rows);
+    let piece = spawned(&mut game, PieceType::T);
+    let moves = movegen::moves(&game, &piece);
+
+    // 8 spots for each flat orientation and 9 for each upright one
+    assert_eq!(moves.len(), 34);
+    let placements: HashSet<_> = moves.iter().map(|found| finesse::placement(&found.piece))
+        .collect();
+    assert_eq!(placements.len(), moves.len());
+    for found in moves.iter() {
+        assert!(!found.t_spin);
+        assert!(!found.inputs.contains(&Input::SoftDrop), "{:?}", found.inputs);
+        replay(&rows, &piece, found);
+    }
+}
+
+#[test]
+fn tuck_under_an_overhang() {
+    let rows = [
+        "......####",
+        "..........",
+    ];
+    let mut game = board(&rows);
+    let piece = spawned(&mut game, PieceType::Straight);
+    let moves = movegen::moves(&game, &piece);
+
+    let under = cells(vec![(7, 6), (7, 7), (7, 8), (7, 9)]);
+    let tuck = moves.iter().find(|found| finesse::placement(&found.piece) == under)
+        .expect("the tuck is found");
+    // one step left out of the way of the overhang, down to the floor, along it to the right
+    let mut shortest = vec![Input::MoveLeft];
+    shortest.extend([Input::SoftDrop; 6]);
+    shortest.extend([Input::MoveRight; 4]);
+    shortest.push(Input::HardDrop);
+    assert_eq!(tuck.inputs, shortest);
+
+    let placements: HashSet<_> = moves.iter().map(|found| finesse::placement(&found.piece))
+        .collect();
+    assert_eq!(placements.len(), moves.len());
+    for found in moves.iter() {
+        replay(&rows, &piece, found);
+    }
+}
+
+#[test]
+fn t_spin_double() {
+    let rows = [
+        "####......",
+        "###...####",
+        "####.#####",
+    ];
+    let mut game = board(&rows);
+    let piece = spawned(&mut game, PieceType::T);
+    let moves = movegen::moves(&game, &piece);
+
+    let slot = cells(vec![(6, 3), (6, 4), (6, 5), (7, 4)]);
+    let spin = moves.iter().find(|found| found.t_spin && finesse::placement(&found.piece) == slot)
+        .expect("the T-spin is found");
+    // one turn upright somewhere on the way down the slot, the other into it at the bottom
+    assert_eq!(spin.inputs.len(), 8);
+    assert_eq!(spin.inputs.iter().filter(|input| **input == Input::SoftDrop).count(), 5);
+    assert_eq!(spin.inputs[6..], [Input::RotateCw, Input::HardDrop]);
+    // dropped straight in it is no T-spin
+    assert!(!moves.iter().any(|found| !found.t_spin && finesse::placement(&found.piece) == slot));
+
+    // the same cells can be locked with and without a T-spin, but only once each way
+    let placements: HashSet<_> = moves.iter()
+        .map(|found| (finesse::placement(&found.piece), found.t_spin)).collect();
+    assert_eq!(placements.len(), moves.len());
+    for found in moves.iter() {
+        replay(&rows, &piece, found);
+    }
+}
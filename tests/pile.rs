@@ -0,0 +1,117 @@
+// the pile keeps a bitmask per row, these pin down what it did as a grid of cells
+
+use tui_tetris::bot::Bot;
+use tui_tetris::config::Rules;
+use tui_tetris::piece::PieceColor;
+use tui_tetris::pile::{Pile, MAX_WIDTH};
+use tui_tetris::simulate;
+use tui_tetris::tetris::Mode;
+
+// a pile with the given rows at the bottom, a letter is the color of a taken cell
+fn pile(width: usize, height: usize, rows: &[&str]) -> Pile {
+    let mut pile = Pile::new(width, height);
+    let top = height - rows.len();
+    for (i, row) in rows.iter().enumerate() {
+        for (j, cell) in row.chars().enumerate() {
+            let color = match cell {
+                'r' => PieceColor::Red,
+                'b' => PieceColor::Blue,
+                'g' => PieceColor::Green,
+                'y' => PieceColor::Yellow,
+                _ => continue,
+            };
+            pile.rows[top + i] |= 1 << j;
+            pile.colors[(top + i) * width + j] = Some(color);
+        }
+    }
+    pile
+}
+
+// the pile drawn back the same way, from the top
+fn rows(pile: &Pile) -> Vec<String> {
+    pile.colors.chunks(pile.col_count).map(|row| row.iter().map(|cell| match cell {
+        Some(PieceColor::Red) => 'r',
+        Some(PieceColor::Blue) => 'b',
+        Some(PieceColor::Green) => 'g',
+        Some(PieceColor::Yellow) => 'y',
+        Some(_) => '?',
+        None => '.',
+    }).collect()).collect()
+}
+
+#[test]
+fn full_row_at_every_width() {
+    for width in 1..=MAX_WIDTH {
+        let full = pile(width, 2, &[&"r".repeat(width)]);
+        assert_eq!(full.full_row().count_ones() as usize, width);
+        assert_eq!(full.rows[1], full.full_row());
+        assert_eq!(full.full_lines(), vec![1]);
+
+        let gap = pile(width, 2, &[&format!("{}.", "r".repeat(width - 1))]);
+        assert!(gap.full_lines().is_empty());
+    }
+    assert_eq!(Pile::new(MAX_WIDTH, 1).full_row(), u64::MAX);
+}
+
+#[test]
+fn cleared_rows_take_their_colors_with_them() {
+    let mut pile = pile(4, 6, &[
+        "r...",
+        "bbbb",
+        ".g.y",
+        "gggg",
+        "yy.r",
+    ]);
+    assert_eq!(pile.full_lines(), vec![2, 4]);
+    assert_eq!(pile.cleanup_full_lines(), 2);
+    assert_eq!(rows(&pile), vec![
+        "....",
+        "....",
+        "....",
+        "r...",
+        ".g.y",
+        "yy.r",
+    ]);
+    assert_eq!(pile.rows, vec![0, 0, 0, 0b0001, 0b1010, 0b1011]);
+    assert!(pile.contains((4, 3)) && !pile.contains((4, 2)));
+    assert_eq!(pile.cells().count(), 6);
+
+    // nothing more to clear
+    assert_eq!(pile.cleanup_full_lines(), 0);
+    assert_eq!(pile.rows, vec![0, 0, 0, 0b0001, 0b1010, 0b1011]);
+}
+
+#[test]
+fn the_top_row_is_never_cleared_but_is_lost_with_any_clear() {
+    let mut pile = pile(3, 3, &[
+        "rrr",
+        "b..",
+        "ggg",
+    ]);
+    assert_eq!(pile.full_lines(), vec![2]);
+    assert_eq!(pile.cleanup_full_lines(), 1);
+    assert_eq!(rows(&pile), vec!["...", "...", "b.."]);
+}
+
+#[test]
+fn lines_a_piece_would_complete() {
+    let pile = pile(10, 4, &[
+        "rrrr..rrrr",
+        "rrrrr.rrrr",
+        "rrrrr.rrrr",
+    ]);
+    // a row comes once for every cell the piece has in it
+    let complete = |positions| pile.get_complete_lines_with(&positions);
+    assert_eq!(complete([(1, 4), (1, 5), (2, 5), (3, 5)]), vec![1, 1, 2, 3]);
+    assert_eq!(complete([(0, 5), (1, 5), (2, 5), (3, 5)]), vec![2, 3]);
+    assert!(complete([(0, 4), (0, 5), (0, 6), (1, 5)]).is_empty());
+}
+
+// the bot plays the same game it did before the pile was a row of bitmasks
+#[test]
+fn bot_game_is_unchanged() {
+    let rules = Rules { width: 10, ..Rules::default() };
+    let result = simulate::play(&mut Bot::default(), &rules, Mode::Marathon, 0, 60).unwrap();
+    assert_eq!((result.score, result.lines, result.pieces, result.time_ms, result.topped_out),
+               (220, 22, 60, 19350, false));
+}
@@ -0,0 +1,336 @@
+// renders known game states into tui's TestBackend and compares the text on screen
+// with the snapshots in tests/snapshots. `UPDATE_SNAPSHOTS=1 cargo test` writes them anew.
+
+use std::path::PathBuf;
+
+use tui::backend::TestBackend;
+use tui::buffer::Buffer;
+use tui::layout::Rect;
+use tui::style::Color;
+use tui::Terminal;
+
+use std::time::{Duration, Instant};
+
+use tui_tetris::animation::Animations;
+use tui_tetris::config::{Renderer, Rules, Theme, Visuals};
+use tui_tetris::piece::{self, Piece, PieceType};
+use tui_tetris::tetris::{Delay, GameEvent, Input, Mode, Tetris};
+use tui_tetris::theme::{ColorDepth, Colors};
+use tui_tetris::ui;
+
+fn game(mode: Mode, width: usize, height: usize, seed: u64) -> Tetris {
+    let rules = Rules { width, height, preview_count: 2, ..Rules::default() };
+    let mut game = Tetris::new(&rules, mode, seed);
+    game.set_elapsed_ms(0);
+    game
+}
+
+fn play(game: &mut Tetris, inputs: &[Input]) {
+    for input in inputs {
+        game.set_elapsed_ms(game.elapsed_ms() + 250);
+        game.apply(*input);
+    }
+}
+
+fn render(width: u16, height: u16, draw: impl FnOnce(&mut tui::Frame<TestBackend>)) -> Buffer {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).expect("test backend");
+    terminal.draw(draw).expect("test backend");
+    terminal.backend().buffer().clone()
+}
+
+fn text(buffer: &Buffer) -> String {
+    let area = buffer.area;
+    (area.top()..area.bottom()).map(|y| {
+        let line: String = (area.left()..area.right())
+            .map(|x| buffer.get(x, y).symbol.as_str()).collect();
+        line.trim_end().to_string() + "\n"
+    }).collect()
+}
+
+fn assert_snapshot(name: &str, buffer: &Buffer) {
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests", "snapshots", name].iter().collect();
+    let actual = text(buffer);
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::write(&path, &actual).expect("writable snapshot");
+        return;
+    }
+    let expected = std::fs::read_to_string(&path)
+        .unwrap_or_else(|err| panic!("no snapshot {}: {}", path.display(), err));
+    assert!(expected == actual, "{} changed, now it is\n{}", name, actual);
+}
+
+// the background of the top left corner of each block the piece covers on the board
+fn block_colors(buffer: &Buffer, piece: &Piece) -> Vec<Color> {
+    piece.get_positions().iter()
+        .map(|(i, j)| buffer.get(*j as u16 * 4, *i as u16 * 2).bg).collect()
+}
+
+#[test]
+fn fresh_game() {
+    let game = game(Mode::Marathon, 6, 8, 1);
+    let buffer = render(64, 40, |f| ui::draw_game(f, &game, &Visuals::default()));
+    assert_snapshot("fresh_game.txt", &buffer);
+
+    let color = Colors::new(&Visuals::default())
+        .piece(piece::get_piece_color(&game.current_piece.piece_type));
+    assert_eq!(block_colors(&buffer, &game.current_piece), vec![color; 4]);
+    assert_eq!(block_colors(&buffer, &game.projected_piece), vec![Color::Gray; 4]);
+}
+
+#[test]
+fn pile_spare_and_queue() {
+    let mut game = game(Mode::Marathon, 6, 8, 1);
+    play(&mut game, &[Input::MoveLeft, Input::MoveLeft, Input::HardDrop, Input::Hold,
+                      Input::RotateCw, Input::MoveRight, Input::MoveRight, Input::HardDrop,
+                      Input::HardDrop]);
+    let buffer = render(64, 40, |f| ui::draw_game(f, &game, &Visuals::default()));
+    assert_snapshot("pile_spare_and_queue.txt", &buffer);
+}
+
+#[test]
+fn lines_the_ghost_completes_are_greyed() {
+    let mut game = game(Mode::Marathon, 4, 6, 1);
+    game.pile.add(&Piece::new(PieceType::Square, 0, 4));
+    game.current_piece = Piece::new(PieceType::Square, 2, 0);
+    game.project();
+
+    let visuals = Visuals { color_depth: ColorDepth::TrueColor, ..Visuals::default() };
+    let buffer = render(16, 14, |f| ui::draw_board(f, f.size(), &game, &visuals));
+    assert_snapshot("lines_the_ghost_completes.txt", &buffer);
+    assert_eq!(block_colors(&buffer, &Piece::new(PieceType::Square, 0, 4)),
+               vec![Color::Rgb(200, 200, 200); 4]);
+    assert_eq!(block_colors(&buffer, &game.projected_piece), vec![Color::Gray; 4]);
+}
+
+#[test]
+fn trainer_target_and_score_panel() {
+    let mut game = game(Mode::Trainer, 8, 10, 3);
+    play(&mut game, &[Input::MoveLeft, Input::MoveRight, Input::HardDrop]);
+    let buffer = render(80, 40, |f| ui::draw_game(f, &game, &Visuals::default()));
+    assert_snapshot("trainer.txt", &buffer);
+
+    // the ghost is drawn over the target where they overlap
+    let target = game.target.as_ref().expect("the trainer always has a target");
+    let ghost = game.projected_piece.get_positions();
+    let uncovered: Vec<Color> = target.piece.get_positions().iter()
+        .zip(block_colors(&buffer, &target.piece))
+        .filter(|(cell, _)| !ghost.contains(cell)).map(|(_, color)| color).collect();
+    assert!(!uncovered.is_empty());
+    assert!(uncovered.iter().all(|color| *color == Color::White));
+
+    let panel = render(30, 20, |f| ui::draw_score(f, Rect::new(0, 0, 30, 20), &game));
+    assert_snapshot("trainer_score_panel.txt", &panel);
+}
+
+#[test]
+fn monochrome_theme() {
+    let mut game = game(Mode::Marathon, 6, 8, 1);
+    play(&mut game, &[Input::HardDrop]);
+    let visuals = Visuals { theme: Theme::Monochrome, ..Visuals::default() };
+    let buffer = render(64, 40, |f| ui::draw_game(f, &game, &visuals));
+    assert_eq!(block_colors(&buffer, &game.current_piece), vec![Color::White; 4]);
+}
+
+#[test]
+fn compact_renderers_fit_80x24() {
+    let mut game = game(Mode::Marathon, 10, 20, 1);
+    play(&mut game, &[Input::MoveLeft, Input::MoveLeft, Input::HardDrop, Input::Hold,
+                      Input::HardDrop]);
+
+    let compact = Visuals { renderer: Renderer::Compact, ..Visuals::default() };
+    let buffer = render(80, 24, |f| ui::draw_game(f, &game, &compact));
+    assert_snapshot("compact.txt", &buffer);
+    let color = Colors::new(&Visuals::default())
+        .piece(piece::get_piece_color(&game.current_piece.piece_type));
+    for (i, j) in game.current_piece.get_positions().iter() {
+        let cell = buffer.get(1 + 2 * *j as u16, 1 + *i as u16);
+        assert_eq!((cell.symbol.as_str(), cell.fg), ("█", color));
+    }
+
+    // the default board is too tall for two characters per cell, not for half blocks
+    let mut game = self::game(Mode::Marathon, 18, 27, 1);
+    play(&mut game, &[Input::HardDrop]);
+    let half = Visuals { renderer: Renderer::HalfBlock, ..Visuals::default() };
+    let buffer = render(80, 24, |f| ui::draw_game(f, &game, &half));
+    assert_snapshot("halfblock.txt", &buffer);
+}
+
+#[test]
+fn boards_taller_than_the_terminal_are_cut_off() {
+    let mut game = game(Mode::Marathon, 18, 27, 1);
+    play(&mut game, &[Input::HardDrop]);
+    // the default board at 80x24 and terminals resized smaller than that
+    for (width, height) in [(80, 24), (80, 12), (30, 5), (10, 2)] {
+        for renderer in [Renderer::Compact, Renderer::HalfBlock] {
+            let visuals = Visuals { renderer, ..Visuals::default() };
+            let buffer = render(width, height, |f| ui::draw_game(f, &game, &visuals));
+            assert_eq!(buffer.get(0, 0).symbol, "╭", "{:?} at {}x{}", renderer, width, height);
+        }
+    }
+}
+
+#[test]
+fn themes_fall_back_to_what_the_terminal_shows() {
+    assert_eq!(ColorDepth::from_env(Some("truecolor"), Some("xterm-256color")),
+               ColorDepth::TrueColor);
+    assert_eq!(ColorDepth::from_env(None, Some("xterm-256color")), ColorDepth::Indexed);
+    assert_eq!(ColorDepth::from_env(None, Some("linux")), ColorDepth::Basic);
+
+    let mut game = game(Mode::Marathon, 6, 8, 1);
+    play(&mut game, &[Input::HardDrop]);
+    let piece_color = piece::get_piece_color(&game.current_piece.piece_type);
+    let colors_at = |color_depth| {
+        let visuals = Visuals { theme: Theme::Guideline, color_depth, ..Visuals::default() };
+        let buffer = render(64, 40, |f| ui::draw_game(f, &game, &visuals));
+        assert_eq!(block_colors(&buffer, &game.current_piece),
+                   vec![Colors::new(&visuals).piece(piece_color); 4]);
+        Colors::new(&visuals).piece(piece_color)
+    };
+    assert!(matches!(colors_at(ColorDepth::TrueColor), Color::Rgb(..)));
+    assert!(matches!(colors_at(ColorDepth::Indexed), Color::Indexed(16..=255)));
+    assert!(!matches!(colors_at(ColorDepth::Basic), Color::Rgb(..) | Color::Indexed(_)));
+
+    // every theme keeps the pieces apart, but monochrome
+    for theme in Theme::ALL.into_iter().filter(|theme| *theme != Theme::Monochrome) {
+        for color_depth in [ColorDepth::TrueColor, ColorDepth::Indexed, ColorDepth::Basic] {
+            let colors = Colors::new(&Visuals { theme, color_depth, ..Visuals::default() });
+            let pieces = colors.pieces;
+            assert!(pieces.iter().enumerate().all(|(idx, color)| !pieces[..idx].contains(color)),
+                    "{:?} at {:?}: {:?}", theme, color_depth, pieces);
+        }
+    }
+}
+
+#[test]
+fn patterns_tell_the_pieces_apart() {
+    let mut game = game(Mode::Marathon, 10, 20, 1);
+    play(&mut game, &[Input::MoveLeft, Input::MoveLeft, Input::HardDrop, Input::Hold,
+                      Input::HardDrop]);
+    let visuals = Visuals { theme: Theme::Monochrome, renderer: Renderer::Compact,
+                            patterns: true, ..Visuals::default() };
+    let buffer = render(80, 24, |f| ui::draw_game(f, &game, &visuals));
+    assert_snapshot("patterns.txt", &buffer);
+
+    // a pattern is taken apart by characters, whatever their size
+    assert_eq!([0, 1, 2].map(|k| ui::pattern_symbol("▚▞", k)), ["▚", "▞", "▚"]);
+    assert_eq!([0, 1].map(|k| ui::pattern_symbol("#", k)), ["#", "#"]);
+}
+
+#[test]
+fn clears_are_wiped_and_called_out() {
+    let mut game = game(Mode::Marathon, 4, 6, 1);
+    game.pile.add(&Piece::new(PieceType::Square, 0, 4));
+    game.current_piece = Piece::new(PieceType::Square, 2, 0);
+    play(&mut game, &[Input::HardDrop]);
+
+    let events: Vec<GameEvent> = game.take_events().collect();
+    assert_eq!(events, vec![
+        GameEvent::Locked { cells: vec![] },
+        GameEvent::Cleared { rows: vec![4, 5], t_spin: false, back_to_back: false, points: 12,
+                             delay_ms: 0 },
+    ]);
+    assert_eq!(game.take_events().count(), 0);
+
+    let mut animations = Animations::new();
+    let now = Instant::now();
+    for event in events {
+        animations.start(event, now);
+    }
+    let visuals = Visuals { color_depth: ColorDepth::TrueColor, ..Visuals::default() };
+    let overlay = animations.overlay(now);
+    let board = ui::BoardWidget { game: &game, visuals: &visuals, overlay: &overlay };
+    let buffer = render(16, 14, |f| f.render_widget(board, f.size()));
+    assert!(text(&buffer).contains("+12 DOUBLE"), "{}", text(&buffer));
+    let flash = Colors::new(&visuals).flash;
+    assert!((0..4).all(|j| buffer.get(j * 4, 10).bg == flash));
+
+    assert!(animations.overlay(now + Duration::from_secs(1)).callouts.is_empty());
+    assert!(animations.is_empty());
+}
+
+#[test]
+fn cleared_lines_stay_until_the_next_piece_comes_in() {
+    let rules = Rules { width: 4, height: 6, line_clear_delay_ms: 300, entry_delay_ms: 200,
+                        ..Rules::default() };
+    let mut game = Tetris::new(&rules, Mode::Marathon, 1);
+    game.set_elapsed_ms(0);
+    game.pile.add(&Piece::new(PieceType::Square, 0, 4));
+    game.current_piece = Piece::new(PieceType::Square, 2, 0);
+    game.apply(Input::HardDrop);
+    assert_eq!(game.delay, Some(Delay::LineClear { rows: vec![4, 5], until_ms: 300 }));
+    assert_eq!((game.lines, game.get_timeout()), (2, 300));
+
+    // the full rows are still there and nothing is in play
+    let buffer = render(16, 14, |f| ui::draw_board(f, f.size(), &game, &Visuals::default()));
+    let clearing = Colors::new(&Visuals::default()).clearing;
+    assert!((0..4).all(|j| buffer.get(j * 4, 8).bg == clearing));
+    assert!((0..4).all(|i| buffer.get(4, i * 2).bg == Color::Reset));
+
+    let next = game.next_pieces[0].piece_type;
+    game.set_elapsed_ms(250);
+    game.apply(Input::MoveLeft);
+    assert_eq!(game.stats.keys, 1);
+    game.set_elapsed_ms(320);
+    game.apply(Input::MoveLeft);
+    assert_eq!(game.delay, Some(Delay::Entry { until_ms: 500 }));
+    assert!(game.pile.to_vec().iter().all(|taken| !taken));
+
+    game.set_elapsed_ms(500);
+    game.update();
+    assert_eq!((game.delay.as_ref(), game.current_piece.piece_type), (None, next));
+    game.apply(Input::MoveLeft);
+    assert_eq!(game.stats.keys, 2);
+}
+
+// a straight that finishes the bottom row keeps three cells, one row lower once the row is gone
+#[test]
+fn locked_cells_with_and_without_a_clear_delay() {
+    for (line_clear_delay_ms, cells) in [(0, vec![(3, 3), (4, 3), (5, 3)]),
+                                         (300, vec![(2, 3), (3, 3), (4, 3)])] {
+        let rules = Rules { width: 4, height: 6, line_clear_delay_ms, ..Rules::default() };
+        let mut game = Tetris::new(&rules, Mode::Marathon, 1);
+        game.set_elapsed_ms(0);
+        game.pile.rows[5] = 0b0111;
+        game.current_piece = Piece::new(PieceType::Straight, 1, 0);
+        game.current_piece.rotate_clockwise();
+        game.apply(Input::HardDrop);
+
+        let events: Vec<GameEvent> = game.take_events().collect();
+        assert_eq!(events[0], GameEvent::Locked { cells: cells.clone() }, "{:?}", events);
+        assert!(cells.iter().all(|&cell| game.pile.contains(cell)));
+    }
+}
+
+#[test]
+fn a_lock_that_clears_is_lit_above_the_line_still_on_the_board() {
+    let rules = Rules { width: 4, height: 6, line_clear_delay_ms: 300, ..Rules::default() };
+    let mut game = Tetris::new(&rules, Mode::Marathon, 1);
+    game.set_elapsed_ms(0);
+    game.pile.rows[5] = 0b0111;
+    for j in 0..3 {
+        game.pile.colors[5 * 4 + j] = Some(piece::get_piece_color(&PieceType::Square));
+    }
+    game.current_piece = Piece::new(PieceType::Straight, 1, 0);
+    game.current_piece.rotate_clockwise();
+    game.apply(Input::HardDrop);
+
+    let mut animations = Animations::new();
+    let now = Instant::now();
+    for event in game.take_events() {
+        animations.start(event, now);
+    }
+    let overlay = animations.overlay(now + Duration::from_millis(100));
+    assert_eq!(overlay.locked, vec![(2, 3), (3, 3), (4, 3)]);
+    assert_eq!(overlay.wipes.iter().map(|(row, _)| *row).collect::<Vec<_>>(), vec![5]);
+    assert!(overlay.locked.iter().all(|&cell| game.pile.contains(cell)));
+
+    // the piece is lit where it is, the full row under it is wiped from its ends. The callout
+    // is on the top half of the row.
+    let visuals = Visuals { color_depth: ColorDepth::TrueColor, ..Visuals::default() };
+    let colors = Colors::new(&visuals);
+    let board = ui::BoardWidget { game: &game, visuals: &visuals, overlay: &overlay };
+    let buffer = render(16, 14, |f| f.render_widget(board, f.size()));
+    assert!((2..5).all(|i| buffer.get(12, i * 2).bg == colors.flash));
+    assert_eq!([0, 1, 2, 3].map(|j| buffer.get(j * 4, 11).bg),
+               [colors.flash, colors.clearing, colors.clearing, colors.flash]);
+}
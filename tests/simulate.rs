@@ -0,0 +1,86 @@
+// running many games at once and what is reported about them
+
+use std::io;
+use std::time::Duration;
+
+use tui_tetris::bot::{Plan, Player};
+use tui_tetris::config::Rules;
+use tui_tetris::simulate::{self, GameResult};
+use tui_tetris::tetris::{Mode, Tetris};
+
+// never finds a spot, every piece is hard dropped where it comes in
+struct Dropper;
+
+impl Player for Dropper {
+    fn plan(&mut self, _game: &Tetris) -> io::Result<Option<Plan>> {
+        Ok(None)
+    }
+}
+
+fn result(seed: u64, score: usize, lines: usize, pieces: usize, topped_out: bool) -> GameResult {
+    GameResult { seed, score, lines, pieces, time_ms: pieces as u64 * 100, topped_out }
+}
+
+#[test]
+fn results_come_in_seed_order() {
+    for threads in [1, 2, 3, 8, 32] {
+        let results = simulate::run(10, threads, 100, || Ok(Box::new(Dropper)), |_, seed| {
+            // the first games take the longest, so they end last
+            std::thread::sleep(Duration::from_millis(110 - seed));
+            Ok(result(seed, 0, 0, 0, false))
+        }).unwrap();
+        let seeds: Vec<u64> = results.iter().map(|result| result.seed).collect();
+        assert_eq!(seeds, (100..110).collect::<Vec<_>>(), "with {} threads", threads);
+    }
+}
+
+#[test]
+fn a_failed_game_fails_the_run() {
+    let run = simulate::run(4, 2, 0, || Ok(Box::new(Dropper)), |_, seed| match seed {
+        2 => Err(io::Error::other("bot went away")),
+        seed => Ok(result(seed, 0, 0, 0, false)),
+    });
+    assert_eq!(run.unwrap_err().to_string(), "bot went away");
+}
+
+#[test]
+fn summary_of_a_few_games() {
+    let results = [
+        result(0, 300, 3, 30, false),
+        result(1, 100, 1, 10, true),
+        result(2, 500, 5, 50, false),
+        result(3, 200, 2, 20, true),
+        result(4, 400, 4, 40, false),
+    ];
+    let summary = simulate::summarize(&results);
+    assert_eq!(summary.games, 5);
+    assert_eq!((summary.mean_lines, summary.mean_score, summary.mean_pieces),
+               (3.0, 300.0, 30.0));
+    assert_eq!(summary.top_out_rate, 0.4);
+    // the nearest score at or below each percentile
+    assert_eq!([summary.score_min, summary.score_p25, summary.score_median, summary.score_p75,
+                summary.score_p90, summary.score_max], [100, 200, 300, 400, 400, 500]);
+
+    assert_eq!(summary.to_csv(),
+               "games,mean_lines,mean_score,mean_pieces,top_out_rate,\
+                score_min,score_p25,score_median,score_p75,score_p90,score_max\n\
+                5,3.000,300.000,30.000,0.4000,100,200,300,400,400,500\n");
+}
+
+#[test]
+fn summary_of_no_games() {
+    let summary = simulate::summarize(&[]);
+    assert_eq!(summary.to_csv().lines().nth(1), Some("0,0.000,0.000,0.000,0.0000,0,0,0,0,0,0"));
+}
+
+#[test]
+fn play_until_topped_out() {
+    let rules = Rules { width: 10, height: 12, ..Rules::default() };
+    let result = simulate::play(&mut Dropper, &rules, Mode::Marathon, 3, 1000).unwrap();
+    assert_eq!(result.seed, 3);
+    assert!(result.topped_out);
+    assert!(result.pieces > 0 && result.pieces < 1000);
+    assert_eq!(result.lines, 0);
+    // one hard drop per turn
+    assert_eq!(result.time_ms, result.pieces as u64 * simulate::INPUT_MS);
+}
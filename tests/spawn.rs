@@ -0,0 +1,166 @@
+// rotations and holds between two pieces, done as the next piece comes in
+
+use std::time::{Duration, Instant};
+
+use tui_tetris::app::Session;
+use tui_tetris::config::Rules;
+use tui_tetris::controls::Action;
+use tui_tetris::input::HeldActions;
+use tui_tetris::piece::{Piece, PieceType};
+use tui_tetris::tetris::{Input, Mode, Spawn, Tetris};
+
+fn rules() -> Rules {
+    Rules { entry_delay_ms: 200, ..Rules::default() }
+}
+
+fn drop_first_piece(game: &mut Tetris) {
+    game.set_elapsed_ms(0);
+    game.current_piece = Piece::new(PieceType::T, 0, 0);
+    game.apply(Input::HardDrop);
+    assert!(game.delay.is_some());
+}
+
+fn waiting_game() -> Tetris {
+    let mut game = Tetris::new(&rules(), Mode::Marathon, 1);
+    drop_first_piece(&mut game);
+    game
+}
+
+#[test]
+fn initial_rotation() {
+    let mut game = waiting_game();
+    let next = game.next_pieces[0].clone();
+    game.set_elapsed_ms(50);
+    game.apply(Input::RotateCw);
+    game.apply(Input::RotateCcw);
+    assert_eq!(game.spawn, Spawn::default());
+    game.apply(Input::Rotate180);
+    game.apply(Input::RotateCcw);
+    assert_eq!(game.spawn, Spawn { quarter_turns: 1, hold: false });
+    assert!(game.delay.is_some());
+
+    game.set_elapsed_ms(200);
+    game.update();
+    assert_eq!(game.delay, None);
+    assert_eq!(game.spawn, Spawn::default());
+    assert_eq!((game.current_piece.piece_type, game.current_piece.rotation),
+               (next.piece_type, (next.rotation + 1) % 4));
+    assert_eq!(game.stats.keys, 5);
+}
+
+#[test]
+fn initial_hold() {
+    let mut game = waiting_game();
+    let (next, spare) = (game.next_pieces[0].piece_type, game.spare_piece.piece_type);
+    game.set_elapsed_ms(150);
+    game.apply(Input::Hold);
+    assert!(game.delay.is_some());
+    game.set_elapsed_ms(200);
+    game.update();
+    assert_eq!(game.delay, None);
+    assert_eq!((game.current_piece.piece_type, game.spare_piece.piece_type), (spare, next));
+    assert!(game.spare_used);
+
+    // the hold was used on the way in
+    game.apply(Input::Hold);
+    assert_eq!(game.current_piece.piece_type, spare);
+}
+
+#[test]
+fn rotation_and_hold_together() {
+    let mut game = waiting_game();
+    let (next, spare) = (game.next_pieces[0].piece_type, game.spare_piece.piece_type);
+    // both were held when the delay ran out, the held piece comes in turned
+    game.set_elapsed_ms(150);
+    game.apply(Input::RotateCw);
+    game.apply(Input::Hold);
+    assert_eq!(game.spawn, Spawn { quarter_turns: 1, hold: true });
+    game.set_elapsed_ms(200);
+    game.update();
+    assert_eq!(game.delay, None);
+    assert_eq!((game.current_piece.piece_type, game.current_piece.rotation), (spare, 1));
+    assert_eq!((game.spare_piece.piece_type, game.spare_piece.rotation), (next, 0));
+    assert!(game.spare_used);
+    assert_eq!(game.stats.keys, 3);
+}
+
+#[test]
+fn input_after_the_delay_goes_to_the_piece_that_is_due() {
+    let mut game = waiting_game();
+    let (next, spare) = (game.next_pieces[0].clone(), game.spare_piece.piece_type);
+    game.set_elapsed_ms(250);
+    game.apply(Input::RotateCw);
+    assert_eq!((game.delay.as_ref(), game.spawn), (None, Spawn::default()));
+    assert_eq!((game.current_piece.piece_type, game.current_piece.rotation),
+               (next.piece_type, (next.rotation + 1) % 4));
+
+    // a plain hold of the piece that came in, the spare comes in as it was kept
+    game.apply(Input::Hold);
+    assert_eq!((game.current_piece.piece_type, game.current_piece.rotation), (spare, 0));
+    assert_eq!(game.spare_piece.piece_type, next.piece_type);
+    assert_eq!(game.stats.keys, 3);
+}
+
+#[test]
+fn rotation_pressed_as_the_piece_comes_in_is_done_once() {
+    let mut session = Session::new(&rules(), Mode::Marathon, 1);
+    drop_first_piece(&mut session.game);
+    let next = session.game.next_pieces[0].clone();
+    let start = Instant::now();
+    let mut held = HeldActions::new();
+
+    // pressed after the delay ran out, before the game caught up with it
+    session.game.set_elapsed_ms(250);
+    let now = start + Duration::from_millis(250);
+    assert!(!session.press(&mut held, Action::RotateCw, start, now));
+    assert_eq!(session.game.delay, None);
+    assert_eq!((session.game.current_piece.piece_type, session.game.current_piece.rotation),
+               (next.piece_type, (next.rotation + 1) % 4));
+    assert_eq!(session.game.stats.keys, 2);
+
+    // other keys go on to the piece that came in
+    assert!(session.press(&mut held, Action::MoveLeft, start, now));
+}
+
+#[test]
+fn rotation_and_hold_held_into_the_piece_are_replayed() {
+    let mut session = Session::new(&rules(), Mode::Marathon, 1);
+    session.game.set_elapsed_ms(0);
+    session.recording.record(0, Input::HardDrop, false);
+    session.game.apply(Input::HardDrop);
+    let spare = session.game.spare_piece.piece_type;
+    let start = Instant::now();
+    let mut held = HeldActions::new();
+
+    // the rotation was held down through the delay, the hold pressed after it ran out
+    held.press(Action::RotateCw, start + Duration::from_millis(100));
+    session.game.set_elapsed_ms(250);
+    assert!(!session.press(&mut held, Action::Hold, start, start + Duration::from_millis(250)));
+    let piece = &session.game.current_piece;
+    assert_eq!((piece.piece_type, piece.rotation, session.game.stats.keys), (spare, 1, 3));
+
+    // recorded from before the piece came in, so the replay keeps them for it too
+    session.recording.finish(250);
+    let mut replayed = session.recording.play_all();
+    replayed.update();
+    let piece = &replayed.current_piece;
+    assert_eq!((piece.piece_type, piece.rotation, replayed.stats.keys), (spare, 1, 3));
+}
+
+#[test]
+fn held_actions_follow_the_key_repeat() {
+    let start = Instant::now();
+    let at = |ms| start + Duration::from_millis(ms);
+    let mut held = HeldActions::new();
+
+    // a single tap is not held, unless it came after the moment asked about
+    held.press(Action::RotateCw, at(0));
+    assert_eq!(held.held(at(100), at(100)).count(), 0);
+    assert_eq!(held.held(at(0), at(100)).collect::<Vec<_>>(), vec![Action::RotateCw]);
+
+    // repeats keep it held, until they stop
+    held.press(Action::Hold, at(0));
+    held.press(Action::Hold, at(100));
+    assert_eq!(held.held(at(200), at(200)).collect::<Vec<_>>(), vec![Action::Hold]);
+    assert_eq!(held.held(at(300), at(300)).count(), 0);
+}
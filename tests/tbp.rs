@@ -0,0 +1,65 @@
+use std::io;
+use std::time::{Duration, Instant};
+
+use tui_tetris::bot::Player;
+use tui_tetris::config::Rules;
+use tui_tetris::tbp::{FrontendMessage, TbpBot};
+use tui_tetris::tetris::{finesse, Input, Mode, Tetris};
+
+#[test]
+fn mock_bot_plays_through_the_protocol() {
+    let rules = Rules { width: 10, ..Rules::default() };
+    let mut game = Tetris::new(&rules, Mode::Marathon, 7);
+    let mut bot = TbpBot::spawn(env!("CARGO_BIN_EXE_tbp-mock-bot")).unwrap();
+    assert_eq!(bot.name, "mock");
+
+    for turn in 0..40 {
+        // now and then the player drops a piece, the bot has to catch up with the game
+        if turn % 10 == 9 {
+            game.apply(Input::HardDrop);
+            continue;
+        }
+
+        let plan = bot.plan(&game).unwrap().expect("the mock bot always finds a spot");
+        assert_eq!(plan.inputs.last(), Some(&Input::HardDrop));
+        let pieces = game.stats.pieces;
+        let cleared = game.lines;
+        for input in plan.inputs {
+            game.apply(input);
+        }
+
+        assert_eq!(game.stats.pieces, pieces + 1);
+        if game.lines == cleared {
+            assert!(finesse::placement(&plan.piece).iter().all(|&cell| game.pile.contains(cell)));
+        }
+        assert!(!game.is_over());
+    }
+    assert!(game.lines > 0);
+}
+
+#[test]
+fn a_stuck_bot_is_given_up_on_and_killed() {
+    let game = Tetris::new(&Rules::default(), Mode::Marathon, 7);
+    let command = format!("{} --stuck", env!("CARGO_BIN_EXE_tbp-mock-bot"));
+    let mut bot = TbpBot::spawn(&command).unwrap();
+    bot.timeout = Duration::from_millis(200);
+    let error = bot.plan(&game).err().expect("no answer");
+    assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+
+    // it ignores the quit too
+    let dropped = Instant::now();
+    drop(bot);
+    assert!(dropped.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn the_hold_is_sent_only_while_it_can_be_used() {
+    let mut game = Tetris::new(&Rules::default(), Mode::Marathon, 7);
+    let hold = |game: &Tetris| match FrontendMessage::start(game) {
+        FrontendMessage::Start { hold, .. } => hold,
+        other => panic!("{:?}", other),
+    };
+    assert_eq!(hold(&game), Some(game.spare_piece.piece_type.letter()));
+    game.apply(Input::Hold);
+    assert_eq!(hold(&game), None);
+}